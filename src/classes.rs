@@ -0,0 +1,206 @@
+use crate::common::decode_escaped;
+use crate::common::decode_hex;
+use crate::config::Statement;
+use crate::expr::parse_expression;
+use crate::expr::Expression;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+use crate::parser::parse_statement;
+use crate::parser::ConfigKeyword;
+use crate::parser::Context;
+
+/// How clients are matched into a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassMatch {
+    /// `match if expression;`: clients for which the condition holds.
+    If(Expression),
+    /// `match expression;`: clients whose value of the expression equals the
+    /// value of one of the subclasses.
+    Value(Expression),
+}
+
+/// A `class` declaration, either from dhcpd.conf or as recorded in
+/// `dhcpd.leases` for a class created through OMAPI:
+/// `class "name" { dynamic; lease limit 4; }`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClassDecl {
+    pub name: String,
+    pub matches: Option<ClassMatch>,
+    /// `spawn with expression;`: subclasses are created for each distinct
+    /// value of the expression.
+    pub spawn_with: Option<Expression>,
+    pub lease_limit: Option<u32>,
+    pub dynamic: bool,
+    pub deleted: bool,
+    /// Other statements of the class body.
+    pub statements: Vec<Statement>,
+}
+
+/// A `subclass` declaration, e.g. one spawned by a class with `spawn with`:
+/// `subclass "name" 1:0:c:29:ab:cd:ef { dynamic; lease limit 2; }`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SubclassDecl {
+    /// Name of the class this is a subclass of.
+    pub class: String,
+    /// Data matched against the `match`/`spawn with` expression of the class.
+    pub value: Vec<u8>,
+    pub lease_limit: Option<u32>,
+    pub dynamic: bool,
+    pub deleted: bool,
+    /// Other statements of the class body.
+    pub statements: Vec<Statement>,
+}
+
+/// Class statements shared by `class` and `subclass` bodies.
+#[derive(Default)]
+struct ClassBody {
+    matches: Option<ClassMatch>,
+    spawn_with: Option<Expression>,
+    lease_limit: Option<u32>,
+    dynamic: bool,
+    deleted: bool,
+    statements: Vec<Statement>,
+}
+
+/// Parse a class body after the opening `{`, including the closing `}`.
+fn parse_class_body(iter: &mut Cursor, ctx: &mut Context) -> Result<ClassBody, String> {
+    let mut body = ClassBody::default();
+
+    loop {
+        match iter.peek().copied() {
+            None => return Err("Unexpected EOF inside class block".to_owned()),
+            Some(LexItem::Paren('}')) => {
+                iter.next();
+                return Ok(body);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "dynamic" => {
+                iter.next();
+                body.dynamic = true;
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "deleted" => {
+                iter.next();
+                body.deleted = true;
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "match" => {
+                iter.next();
+                body.matches = Some(match iter.peek() {
+                    Some(LexItem::Word(w)) if w.as_str() == "if" => {
+                        iter.next();
+                        ClassMatch::If(parse_expression(iter)?)
+                    }
+                    _ => ClassMatch::Value(parse_expression(iter)?),
+                });
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "spawn" => {
+                iter.next();
+                match iter.next() {
+                    Some(LexItem::Word(w)) if w.as_str() == "with" => {}
+                    other => return Err(format!("Expected 'with' after 'spawn', got {:?}", other)),
+                }
+                body.spawn_with = Some(parse_expression(iter)?);
+            }
+            Some(LexItem::Decl(ConfigKeyword::Lease)) => {
+                iter.next();
+                match iter.next() {
+                    Some(LexItem::Word(w)) if w.as_str() == "limit" => {}
+                    other => {
+                        return Err(format!("Expected 'limit' after 'lease', got {:?}", other))
+                    }
+                }
+                body.lease_limit = match iter.next() {
+                    Some(LexItem::Word(v)) => Some(
+                        v.parse::<u32>()
+                            .map_err(|_| format!("Invalid lease limit {:?}", v))?,
+                    ),
+                    other => return Err(format!("Expected lease limit, got {:?}", other)),
+                };
+            }
+            Some(_) => {
+                if let Some(statement) = parse_statement(iter, ctx)? {
+                    body.statements.push(statement);
+                }
+                continue;
+            }
+        }
+        if iter.next() != Some(&LexItem::Endl) {
+            return Err("Semicolon expected inside class block".to_owned());
+        }
+    }
+}
+
+/// Parse the class name following `class` or `subclass`.
+fn parse_class_name<'l>(iter: &mut Cursor<'l>) -> Result<String, String> {
+    match iter.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => Ok(w.clone()),
+        other => Err(format!("Expected class name, got {:?}", other)),
+    }
+}
+
+/// Parse a `class` declaration starting at its keyword, including the closing
+/// `}` or `;`.
+pub(crate) fn parse_class(iter: &mut Cursor, ctx: &mut Context) -> Result<ClassDecl, String> {
+    iter.next(); // "class"
+    let name = parse_class_name(iter)?;
+    let mut class = ClassDecl {
+        name,
+        ..ClassDecl::default()
+    };
+
+    match iter.next() {
+        Some(LexItem::Paren('{')) => {
+            let body = parse_class_body(iter, ctx)?;
+            class.matches = body.matches;
+            class.spawn_with = body.spawn_with;
+            class.lease_limit = body.lease_limit;
+            class.dynamic = body.dynamic;
+            class.deleted = body.deleted;
+            class.statements = body.statements;
+        }
+        Some(LexItem::Endl) => {}
+        other => return Err(format!("Expected '{{' after class name, got {:?}", other)),
+    }
+
+    Ok(class)
+}
+
+/// Parse a `subclass` declaration starting at its keyword, including the
+/// closing `}` or `;`.
+pub(crate) fn parse_subclass(iter: &mut Cursor, ctx: &mut Context) -> Result<SubclassDecl, String> {
+    iter.next(); // "subclass"
+    let class = parse_class_name(iter)?;
+    let value = match iter.next() {
+        Some(LexItem::Str(v)) => decode_escaped(v)?,
+        Some(LexItem::Word(v)) => decode_hex(v)?,
+        other => return Err(format!("Expected subclass value, got {:?}", other)),
+    };
+    let mut subclass = SubclassDecl {
+        class,
+        value,
+        ..SubclassDecl::default()
+    };
+
+    match iter.next() {
+        Some(LexItem::Paren('{')) => {
+            let body = parse_class_body(iter, ctx)?;
+            if body.matches.is_some() || body.spawn_with.is_some() {
+                return Err(format!(
+                    "Subclass of {:?} cannot have match or spawn statements",
+                    subclass.class
+                ));
+            }
+            subclass.lease_limit = body.lease_limit;
+            subclass.dynamic = body.dynamic;
+            subclass.deleted = body.deleted;
+            subclass.statements = body.statements;
+        }
+        Some(LexItem::Endl) => {}
+        other => {
+            return Err(format!(
+                "Expected '{{' after subclass value, got {:?}",
+                other
+            ))
+        }
+    }
+
+    Ok(subclass)
+}
@@ -1,5 +1,30 @@
 use std::cmp;
 use std::fmt;
+use std::sync::Arc;
+
+/// Location of a syntax element in the parsed input.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Byte offset of the first character
+    pub start: usize,
+    /// Byte offset just past the last character
+    pub end: usize,
+    /// Line of the first character, starting at 1
+    pub line: usize,
+    /// Column of the first character, starting at 1
+    pub column: usize,
+    /// File the input was read from, if it came from a file loader
+    pub file: Option<Arc<str>>,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}, ", file)?;
+        }
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Copy)]
 pub struct Date {
@@ -161,38 +186,155 @@ impl fmt::Display for Date {
     }
 }
 
+impl Default for Date {
+    fn default() -> Self {
+        Date::new()
+    }
+}
+
 impl cmp::PartialOrd for Date {
     fn partial_cmp(&self, other: &Date) -> Option<cmp::Ordering> {
-        if self.year != other.year {
-            return self.year.partial_cmp(&other.year);
-        }
+        Some(self.cmp(other))
+    }
+}
 
-        if self.month != other.month {
-            return self.month.partial_cmp(&other.month);
-        }
+impl cmp::Ord for Date {
+    fn cmp(&self, other: &Date) -> cmp::Ordering {
+        (
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        )
+            .cmp(&(
+                other.year,
+                other.month,
+                other.day,
+                other.hour,
+                other.minute,
+                other.second,
+            ))
+    }
+}
+
+/// Decode the contents of a quoted string as written by dhcpd into raw bytes.
+///
+/// Non-printable bytes are written as three digit octal escapes (`\001`),
+/// `\t`, `\n` and `\r` stand for the control characters and any other escaped
+/// character (dhcpd writes `\'`, `\$` and `` \` ``) for itself.
+pub fn decode_escaped<S: AsRef<str>>(input: S) -> Result<Vec<u8>, String> {
+    let input_s = input.as_ref();
+    let mut result = Vec::new();
 
-        if self.day != other.day {
-            return self.day.partial_cmp(&other.day);
+    let mut it = input_s.chars().peekable();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
         }
 
-        if self.hour != other.hour {
-            return self.hour.partial_cmp(&other.hour);
+        match it.peek() {
+            Some(d) if d.is_digit(8) => {
+                let mut v = 0u32;
+                for _ in 0..3 {
+                    match it.next().and_then(|d| d.to_digit(8)) {
+                        Some(d) => v = v * 8 + d,
+                        None => return Err(format!("Invalid octal escape in {:?}", input_s)),
+                    }
+                }
+                if v > 0xff {
+                    return Err(format!("Octal escape out of range in {:?}", input_s));
+                }
+                result.push(v as u8);
+            }
+            Some('t') => {
+                it.next();
+                result.push(b'\t');
+            }
+            Some('n') => {
+                it.next();
+                result.push(b'\n');
+            }
+            Some('r') => {
+                it.next();
+                result.push(b'\r');
+            }
+            Some(&c) => {
+                it.next();
+                let mut buf = [0u8; 4];
+                result.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+            None => result.push(b'\\'),
         }
+    }
 
-        if self.minute != other.minute {
-            return self.minute.partial_cmp(&other.minute);
+    Ok(result)
+}
+
+/// Reverse of [`decode_escaped`]: the contents of a quoted string standing for
+/// `bytes`. Printable characters stand for themselves, all other bytes as
+/// well as `"` and `\` are written as three digit octal escapes.
+pub fn encode_escaped(bytes: &[u8]) -> String {
+    let mut result = String::new();
+
+    for &b in bytes {
+        match b {
+            b'"' | b'\\' => result.push_str(&format!("\\{:03o}", b)),
+            b' '..=b'~' => result.push(b as char),
+            _ => result.push_str(&format!("\\{:03o}", b)),
         }
+    }
 
-        if self.second != other.second {
-            return self.second.partial_cmp(&other.second);
+    result
+}
+
+/// Decode colon-separated hex bytes as written by dhcpd, e.g. `1:0:c:29:ab`.
+/// Each byte is written with one or two hex digits.
+pub fn decode_hex<S: AsRef<str>>(input: S) -> Result<Vec<u8>, String> {
+    let input_s = input.as_ref();
+    let mut result = Vec::new();
+
+    for part in input_s.split(':') {
+        if part.is_empty() || part.len() > 2 {
+            return Err(format!("{:?} is not a colon-separated hex string", input_s));
         }
+        let b = u8::from_str_radix(part, 16)
+            .map_err(|_| format!("{:?} is not a colon-separated hex string", input_s))?;
+        result.push(b);
+    }
+
+    Ok(result)
+}
 
-        None
+/// Write a string as a double-quoted dhcpd string, so that the lexer reads it
+/// back unchanged.
+pub fn quote<S: AsRef<str>>(input: S) -> String {
+    let mut result = String::from("\"");
+
+    let mut it = input.as_ref().chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            '"' => result.push_str("\\\""),
+            // Other backslashes are kept as escape sequences by the lexer.
+            '\\' if matches!(it.peek(), None | Some('\\') | Some('"')) => result.push_str("\\\\"),
+            c => result.push(c),
+        }
     }
+
+    result.push('"');
+    result
 }
 
-impl cmp::Ord for Date {
-    fn cmp(&self, other: &Date) -> cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+/// Reverse of [`quote`]: the contents of a double-quoted string as written
+/// in a [`Parameter`](crate::config::Parameter) argument. Unquoted words are
+/// returned as they are.
+pub fn unquote<S: AsRef<str>>(input: S) -> String {
+    let input = input.as_ref();
+    match input.strip_prefix('"').and_then(|a| a.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => input.to_owned(),
     }
 }
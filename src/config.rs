@@ -0,0 +1,1157 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+
+use crate::classes::ClassDecl;
+use crate::classes::SubclassDecl;
+use crate::common::quote;
+use crate::common::Date;
+use crate::common::Span;
+use crate::ddns::DdnsZone;
+use crate::ddns::TsigKey;
+use crate::dialect::Dialect;
+use crate::duid::Duid;
+use crate::expr::parse_expression;
+use crate::expr::Expression;
+use crate::failover::parse_failover_peer_name;
+use crate::failover::FailoverPeer;
+use crate::failover::FailoverPeerState;
+use crate::include::Include;
+use crate::leases::parse_date;
+use crate::leases::Lease;
+use crate::leases6::parse_prefix;
+use crate::leases6::ByteOrder;
+use crate::leases6::Ia;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+use crate::options::OptionDefinition;
+use crate::options::OptionSpace;
+use crate::options::OptionStatement;
+use crate::parser::parse_declarations;
+use crate::parser::parse_statement;
+use crate::parser::Context;
+use crate::parser::Host;
+use crate::server::ServerParameters;
+
+/// A parsed configuration file: the statements of its global scope.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Config {
+    pub statements: Vec<Statement>,
+    /// Typed view of the common server parameters among the statements.
+    pub parameters: ServerParameters,
+    /// Dialect the input was parsed as: the requested one, or the detected
+    /// one when auto-detecting. `None` if nothing specific to either was
+    /// found.
+    pub dialect: Option<Dialect>,
+}
+
+/// A statement or declaration together with its location in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementKind {
+    SharedNetwork(SharedNetwork),
+    Subnet(Subnet),
+    Subnet6(Subnet6),
+    Group(Group),
+    Pool(Pool),
+    Host(Host),
+    Class(ClassDecl),
+    Subclass(SubclassDecl),
+    Range(Range),
+    Range6(Range6),
+    Prefix6(Prefix6),
+    Option(OptionStatement),
+    OptionSpace(OptionSpace),
+    OptionDefinition(OptionDefinition),
+    If(If),
+    On(On),
+    Set(Set),
+    /// `unset name;`
+    Unset(String),
+    Include(Include),
+    Parameter(Parameter),
+    Unknown(Unknown),
+    // Entries of the leases file
+    Lease(Lease),
+    Ia(Ia),
+    FailoverPeerState(FailoverPeerState),
+    FailoverPeer(FailoverPeer),
+    Key(TsigKey),
+    Zone(DdnsZone),
+    AuthoringByteOrder(ByteOrder),
+    ServerDuid(Duid),
+}
+
+impl Statement {
+    /// Statements nested inside this declaration, empty for simple statements.
+    pub fn children(&self) -> &[Statement] {
+        match &self.kind {
+            StatementKind::SharedNetwork(v) => &v.statements,
+            StatementKind::Subnet(v) => &v.statements,
+            StatementKind::Subnet6(v) => &v.statements,
+            StatementKind::Group(v) => &v.statements,
+            StatementKind::Pool(v) => &v.statements,
+            StatementKind::Class(v) => &v.statements,
+            StatementKind::Subclass(v) => &v.statements,
+            StatementKind::On(v) => &v.statements,
+            StatementKind::Include(v) => v.statements.as_deref().unwrap_or(&[]),
+            _ => &[],
+        }
+    }
+}
+
+/// `shared-network name { ... }`: subnets sharing one physical network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedNetwork {
+    pub name: String,
+    pub statements: Vec<Statement>,
+}
+
+/// `subnet 10.0.0.0 netmask 255.255.255.0 { ... }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subnet {
+    pub network: Ipv4Addr,
+    pub netmask: Ipv4Addr,
+    /// Length of the network prefix, derived from `netmask`.
+    pub prefix_len: u8,
+    pub statements: Vec<Statement>,
+}
+
+impl Subnet {
+    /// The subnet in CIDR notation, e.g. `10.0.0.0/24`.
+    pub fn cidr(&self) -> String {
+        format!("{}/{}", self.network, self.prefix_len)
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        u32::from(ip) & u32::from(self.netmask) == u32::from(self.network)
+    }
+}
+
+/// `subnet6 2001:db8::/64 { ... }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subnet6 {
+    pub network: Ipv6Addr,
+    pub prefix_len: u8,
+    pub statements: Vec<Statement>,
+}
+
+impl Subnet6 {
+    /// The subnet in CIDR notation, e.g. `2001:db8::/64`.
+    pub fn cidr(&self) -> String {
+        format!("{}/{}", self.network, self.prefix_len)
+    }
+
+    pub fn contains(&self, ip: Ipv6Addr) -> bool {
+        u128::from(ip) & prefix_mask6(self.prefix_len) == u128::from(self.network)
+    }
+}
+
+/// Netmask of an IPv6 prefix of length `prefix_len`.
+pub(crate) fn prefix_mask6(prefix_len: u8) -> u128 {
+    u128::MAX
+        .checked_shl(128 - u32::from(prefix_len))
+        .unwrap_or(0)
+}
+
+/// `group { ... }`: statements applied to the declarations inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    pub name: Option<String>,
+    pub statements: Vec<Statement>,
+}
+
+/// `pool { ... }`: an address pool inside a subnet or shared network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pool {
+    /// Name of the peer from `failover peer "name";`.
+    pub failover_peer: Option<String>,
+    /// `allow`/`deny` permits, in order.
+    pub permits: Vec<Permit>,
+    pub statements: Vec<Statement>,
+}
+
+/// Clients a pool permit applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermitKind {
+    KnownClients,
+    UnknownClients,
+    /// `members of "class"`
+    MembersOf(String),
+    DynamicBootpClients,
+    AuthenticatedClients,
+    UnauthenticatedClients,
+    AllClients,
+    /// `after date`: any client, once the date has passed
+    After(Date),
+}
+
+impl fmt::Display for PermitKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PermitKind::KnownClients => write!(f, "known-clients"),
+            PermitKind::UnknownClients => write!(f, "unknown-clients"),
+            PermitKind::MembersOf(v) => write!(f, "members of {}", quote(v)),
+            PermitKind::DynamicBootpClients => write!(f, "dynamic bootp clients"),
+            PermitKind::AuthenticatedClients => write!(f, "authenticated clients"),
+            PermitKind::UnauthenticatedClients => write!(f, "unauthenticated clients"),
+            PermitKind::AllClients => write!(f, "all clients"),
+            PermitKind::After(d) => write!(
+                f,
+                "after {} {}/{:0>2}/{:0>2} {:0>2}:{:0>2}:{:0>2}",
+                d.weekday, d.year, d.month, d.day, d.hour, d.minute, d.second
+            ),
+        }
+    }
+}
+
+/// `allow ...;` or `deny ...;` inside a pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permit {
+    pub allow: bool,
+    pub kind: PermitKind,
+}
+
+impl fmt::Display for Permit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let action = if self.allow { "allow" } else { "deny" };
+        write!(f, "{} {};", action, self.kind)
+    }
+}
+
+/// `range [dynamic-bootp] low [high];`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range {
+    pub dynamic_bootp: bool,
+    pub low: Ipv4Addr,
+    /// Same as `low` for a single-address range.
+    pub high: Ipv4Addr,
+}
+
+impl Range {
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        self.low <= ip && ip <= self.high
+    }
+}
+
+/// `range6 low high;`, `range6 prefix/len;` or `range6 address|prefix/len
+/// temporary;`. A prefix is stored as the range of addresses it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range6 {
+    pub low: Ipv6Addr,
+    pub high: Ipv6Addr,
+    /// Addresses for temporary (IA_TA) assignments.
+    pub temporary: bool,
+}
+
+impl Range6 {
+    pub fn contains(&self, ip: Ipv6Addr) -> bool {
+        self.low <= ip && ip <= self.high
+    }
+}
+
+/// `prefix6 low high /len;`: prefixes of length `prefix_len` delegated from
+/// between `low` and `high`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefix6 {
+    pub low: Ipv6Addr,
+    pub high: Ipv6Addr,
+    pub prefix_len: u8,
+}
+
+impl Prefix6 {
+    /// Whether `prefix` is one of the prefixes delegated from this pool.
+    pub fn contains(&self, prefix: (Ipv6Addr, u8)) -> bool {
+        let (network, len) = prefix;
+        len == self.prefix_len && self.low <= network && network <= self.high
+    }
+}
+
+/// Access to the declarations of a scope: the global scope of a [`Config`]
+/// and the bodies of `shared-network`, `subnet`, `subnet6`, `group` and
+/// `pool`.
+///
+/// `group` only applies common statements to its members, so declarations
+/// inside nested groups are returned as if declared in the scope itself.
+pub trait Scope {
+    fn statements(&self) -> &[Statement];
+
+    fn shared_networks(&self) -> Vec<&SharedNetwork> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::SharedNetwork(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn subnets(&self) -> Vec<&Subnet> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Subnet(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn pools(&self) -> Vec<&Pool> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Pool(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn ranges(&self) -> Vec<&Range> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Range(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn subnets6(&self) -> Vec<&Subnet6> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Subnet6(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn ranges6(&self) -> Vec<&Range6> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Range6(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn prefixes6(&self) -> Vec<&Prefix6> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Prefix6(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn failover_peers(&self) -> Vec<&FailoverPeer> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::FailoverPeer(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn keys(&self) -> Vec<&TsigKey> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Key(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn zones(&self) -> Vec<&DdnsZone> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Zone(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    fn hosts(&self) -> Vec<&Host> {
+        let mut res = Vec::new();
+        walk_groups(self.statements(), &mut |s| {
+            if let StatementKind::Host(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    /// Options set directly in this scope. Unlike the declarations above,
+    /// options of nested groups are not included as they only apply to the
+    /// group's members.
+    fn options(&self) -> Vec<&OptionStatement> {
+        let mut res = Vec::new();
+        walk_includes(self.statements(), &mut |s| {
+            if let StatementKind::Option(v) = &s.kind {
+                res.push(v);
+            }
+        });
+        res
+    }
+
+    /// Value of an option set directly in this scope, looked up by its name
+    /// as written, e.g. `routers` or `dhcp6.name-servers`.
+    fn option(&self, name: &str) -> Option<&OptionStatement> {
+        self.options()
+            .into_iter()
+            .rev()
+            .find(|o| o.full_name() == name)
+    }
+}
+
+/// Visit statements, descending into `group` declarations and included
+/// files.
+fn walk_groups<'a, F>(statements: &'a [Statement], f: &mut F)
+where
+    F: FnMut(&'a Statement),
+{
+    for statement in statements {
+        match &statement.kind {
+            StatementKind::Group(g) => walk_groups(&g.statements, f),
+            StatementKind::Include(_) => walk_groups(statement.children(), f),
+            _ => f(statement),
+        }
+    }
+}
+
+/// Visit statements, descending into included files only.
+pub(crate) fn walk_includes<'a, F>(statements: &'a [Statement], f: &mut F)
+where
+    F: FnMut(&'a Statement),
+{
+    for statement in statements {
+        match &statement.kind {
+            StatementKind::Include(_) => walk_includes(statement.children(), f),
+            _ => f(statement),
+        }
+    }
+}
+
+impl Scope for Config {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Scope for SharedNetwork {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Scope for Subnet {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Scope for Subnet6 {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Scope for Group {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Pool {
+    /// The declaration of the failover peer serving this pool.
+    pub fn peer<'a>(&self, config: &'a Config) -> Option<&'a FailoverPeer> {
+        let name = self.failover_peer.as_ref()?;
+        config
+            .failover_peers()
+            .into_iter()
+            .find(|p| &p.name == name)
+    }
+}
+
+impl DdnsZone {
+    /// The declaration of the key signing updates to this zone.
+    pub fn tsig_key<'a>(&self, config: &'a Config) -> Option<&'a TsigKey> {
+        let name = self.key.as_ref()?;
+        config.keys().into_iter().find(|k| &k.name == name)
+    }
+}
+
+impl Scope for Pool {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+impl Scope for Host {
+    fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+}
+
+/// A declaration enclosing other declarations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeEntry {
+    SharedNetwork(String),
+    Subnet { network: Ipv4Addr, prefix_len: u8 },
+    Subnet6 { network: Ipv6Addr, prefix_len: u8 },
+    Group(Option<String>),
+    Pool,
+    Host(String),
+}
+
+impl fmt::Display for ScopeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScopeEntry::SharedNetwork(v) => write!(f, "shared-network {}", quote(v)),
+            ScopeEntry::Subnet {
+                network,
+                prefix_len,
+            } => write!(f, "subnet {}/{}", network, prefix_len),
+            ScopeEntry::Subnet6 {
+                network,
+                prefix_len,
+            } => write!(f, "subnet6 {}/{}", network, prefix_len),
+            ScopeEntry::Group(Some(v)) => write!(f, "group {}", quote(v)),
+            ScopeEntry::Group(None) => write!(f, "group"),
+            ScopeEntry::Pool => write!(f, "pool"),
+            ScopeEntry::Host(v) => write!(f, "host {}", v),
+        }
+    }
+}
+
+/// Declarations enclosing a statement, outermost first. Empty for the global
+/// scope.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ScopePath {
+    pub entries: Vec<ScopeEntry>,
+}
+
+impl fmt::Display for ScopePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.entries.is_empty() {
+            return write!(f, "global");
+        }
+        let entries: Vec<String> = self.entries.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", entries.join(" > "))
+    }
+}
+
+impl ScopePath {
+    /// Network and prefix length of the innermost enclosing `subnet`.
+    pub fn subnet(&self) -> Option<(Ipv4Addr, u8)> {
+        self.entries.iter().rev().find_map(|e| match e {
+            ScopeEntry::Subnet {
+                network,
+                prefix_len,
+            } => Some((*network, *prefix_len)),
+            _ => None,
+        })
+    }
+
+    /// Network and prefix length of the innermost enclosing `subnet6`.
+    pub fn subnet6(&self) -> Option<(Ipv6Addr, u8)> {
+        self.entries.iter().rev().find_map(|e| match e {
+            ScopeEntry::Subnet6 {
+                network,
+                prefix_len,
+            } => Some((*network, *prefix_len)),
+            _ => None,
+        })
+    }
+
+    /// Name of the innermost enclosing `shared-network`.
+    pub fn shared_network(&self) -> Option<&str> {
+        self.entries.iter().rev().find_map(|e| match e {
+            ScopeEntry::SharedNetwork(v) => Some(v.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// One condition of an `if` statement and the statements it guards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IfBranch {
+    pub condition: Expression,
+    pub statements: Vec<Statement>,
+}
+
+/// `if cond { ... } elsif cond { ... } else { ... }`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct If {
+    /// The `if` branch followed by any `elsif` branches, in order.
+    pub branches: Vec<IfBranch>,
+    pub else_statements: Option<Vec<Statement>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnEvent {
+    Commit,
+    Expiry,
+    Release,
+    Transmission,
+}
+
+impl fmt::Display for OnEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = match self {
+            OnEvent::Commit => "commit",
+            OnEvent::Expiry => "expiry",
+            OnEvent::Release => "release",
+            OnEvent::Transmission => "transmission",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl OnEvent {
+    pub fn from(s: &str) -> Result<OnEvent, String> {
+        match s {
+            "commit" => Ok(OnEvent::Commit),
+            "expiry" => Ok(OnEvent::Expiry),
+            "release" => Ok(OnEvent::Release),
+            "transmission" => Ok(OnEvent::Transmission),
+            _ => Err(format!("'{}' is not a recognized event", s)),
+        }
+    }
+}
+
+/// `on commit or release { ... }`: statements run when a lease event occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct On {
+    pub events: Vec<OnEvent>,
+    pub statements: Vec<Statement>,
+}
+
+/// `set name = expression;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Set {
+    pub name: String,
+    pub value: Expression,
+}
+
+/// Any other simple statement, e.g. `default-lease-time 600;`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    pub name: String,
+    /// Tokens following the name as written, strings quoted.
+    pub args: Vec<String>,
+}
+
+/// A statement or block the library doesn't model, kept in place as
+/// written, e.g. `pool6 { ... }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unknown {
+    /// First token of the statement, empty for a bare `{ ... }` block.
+    pub keyword: String,
+    /// Tokens following the keyword up to the `;` or `{`, strings quoted.
+    pub tokens: Vec<String>,
+    /// Statements of the block, `None` for a statement ending with `;`.
+    /// Nothing inside an unknown block is interpreted.
+    pub children: Option<Vec<Unknown>>,
+    pub span: Span,
+}
+
+fn expect_open_brace(it: &mut Cursor, after: &str) -> Result<(), String> {
+    match it.next() {
+        Some(LexItem::Paren('{')) => Ok(()),
+        other => Err(format!("Expected '{{' after {}, got {:?}", after, other)),
+    }
+}
+
+fn expect_semicolon(it: &mut Cursor, after: &str) -> Result<(), String> {
+    match it.next() {
+        Some(LexItem::Endl) => Ok(()),
+        other => Err(format!("Expected ';' after {}, got {:?}", after, other)),
+    }
+}
+
+fn parse_ipv4(it: &mut Cursor, what: &str) -> Result<Ipv4Addr, String> {
+    match it.next() {
+        Some(LexItem::Word(w)) => w
+            .parse::<Ipv4Addr>()
+            .map_err(|_| format!("Invalid {} {:?}", what, w)),
+        other => Err(format!("Expected {}, got {:?}", what, other)),
+    }
+}
+
+fn parse_ipv6(it: &mut Cursor, what: &str) -> Result<Ipv6Addr, String> {
+    match it.next() {
+        Some(LexItem::Word(w)) => w
+            .parse::<Ipv6Addr>()
+            .map_err(|_| format!("Invalid {} {:?}", what, w)),
+        other => Err(format!("Expected {}, got {:?}", what, other)),
+    }
+}
+
+/// Parse the declarations of a block after its `{`, including the closing
+/// `}`, with `entry` added to the scope of the hosts inside.
+fn parse_scope_body(
+    it: &mut Cursor,
+    ctx: &mut Context,
+    entry: ScopeEntry,
+) -> Result<Vec<Statement>, String> {
+    ctx.scope.push(entry);
+    let statements = parse_declarations(it, ctx, true)?;
+    ctx.scope.pop();
+    Ok(statements)
+}
+
+pub(crate) fn parse_shared_network(
+    it: &mut Cursor,
+    ctx: &mut Context,
+) -> Result<SharedNetwork, String> {
+    it.next(); // "shared-network"
+    let name = match it.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => w.clone(),
+        other => return Err(format!("Expected shared-network name, got {:?}", other)),
+    };
+    expect_open_brace(it, "shared-network name")?;
+    let entry = ScopeEntry::SharedNetwork(name.clone());
+    let statements = parse_scope_body(it, ctx, entry)?;
+
+    Ok(SharedNetwork { name, statements })
+}
+
+pub(crate) fn parse_subnet(it: &mut Cursor, ctx: &mut Context) -> Result<Subnet, String> {
+    it.next(); // "subnet"
+    let network = parse_ipv4(it, "subnet number")?;
+    match it.next() {
+        Some(LexItem::Word(w)) if w.as_str() == "netmask" => {}
+        other => return Err(format!("Expected 'netmask', got {:?}", other)),
+    }
+    let netmask = parse_ipv4(it, "netmask")?;
+    let mask = u32::from(netmask);
+    if mask.leading_ones() != mask.count_ones() {
+        return Err(format!("Netmask {} is not contiguous", netmask));
+    }
+    if u32::from(network) & !mask != 0 {
+        return Err(format!(
+            "Subnet number {} has bits set outside netmask {}",
+            network, netmask
+        ));
+    }
+    expect_open_brace(it, "netmask")?;
+    let prefix_len = mask.leading_ones() as u8;
+    let entry = ScopeEntry::Subnet {
+        network,
+        prefix_len,
+    };
+    let statements = parse_scope_body(it, ctx, entry)?;
+
+    Ok(Subnet {
+        network,
+        netmask,
+        prefix_len,
+        statements,
+    })
+}
+
+pub(crate) fn parse_subnet6(it: &mut Cursor, ctx: &mut Context) -> Result<Subnet6, String> {
+    it.next(); // "subnet6"
+    let (network, prefix_len) = match it.next() {
+        Some(LexItem::Word(w)) => parse_prefix(w)?,
+        other => return Err(format!("Expected subnet6 prefix, got {:?}", other)),
+    };
+    if u128::from(network) & !prefix_mask6(prefix_len) != 0 {
+        return Err(format!(
+            "Subnet6 prefix {}/{} has bits set outside the prefix length",
+            network, prefix_len
+        ));
+    }
+    expect_open_brace(it, "subnet6 prefix")?;
+    let entry = ScopeEntry::Subnet6 {
+        network,
+        prefix_len,
+    };
+    let statements = parse_scope_body(it, ctx, entry)?;
+
+    Ok(Subnet6 {
+        network,
+        prefix_len,
+        statements,
+    })
+}
+
+pub(crate) fn parse_group(it: &mut Cursor, ctx: &mut Context) -> Result<Group, String> {
+    it.next(); // "group"
+    let name = match it.peek().copied() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => {
+            let name = w.clone();
+            it.next();
+            Some(name)
+        }
+        _ => None,
+    };
+    expect_open_brace(it, "group")?;
+    let statements = parse_scope_body(it, ctx, ScopeEntry::Group(name.clone()))?;
+
+    Ok(Group { name, statements })
+}
+
+/// Whether the tokens ahead are an `allow` or `deny` permit, as opposed to
+/// e.g. `allow booting;`.
+fn is_permit(it: &Cursor) -> bool {
+    let mut ahead = it.clone();
+    match ahead.next() {
+        Some(LexItem::Word(w)) if w == "allow" || w == "deny" => {}
+        _ => return false,
+    }
+    matches!(ahead.next(), Some(LexItem::Word(w)) if [
+        "known-clients",
+        "unknown-clients",
+        "known",
+        "unknown",
+        "members",
+        "dynamic",
+        "authenticated",
+        "unauthenticated",
+        "all",
+        "after",
+    ]
+    .contains(&w.as_str()))
+}
+
+fn expect_word(it: &mut Cursor, word: &str, after: &str) -> Result<(), String> {
+    match it.next() {
+        Some(LexItem::Word(w)) if w == word => Ok(()),
+        other => Err(format!(
+            "Expected '{}' after {}, got {:?}",
+            word, after, other
+        )),
+    }
+}
+
+/// Parse an `allow` or `deny` permit, including the `;`.
+fn parse_permit(it: &mut Cursor) -> Result<Permit, String> {
+    let allow = it.next() == Some(&LexItem::Word("allow".to_owned()));
+    let word = match it.next() {
+        Some(LexItem::Word(w)) => w.as_str(),
+        other => return Err(format!("Expected permit, got {:?}", other)),
+    };
+    let kind = match word {
+        "known-clients" => PermitKind::KnownClients,
+        "unknown-clients" => PermitKind::UnknownClients,
+        "known" => {
+            expect_word(it, "clients", word)?;
+            PermitKind::KnownClients
+        }
+        "unknown" => {
+            expect_word(it, "clients", word)?;
+            PermitKind::UnknownClients
+        }
+        "members" => {
+            expect_word(it, "of", word)?;
+            match it.next() {
+                Some(LexItem::Str(v)) | Some(LexItem::Word(v)) => PermitKind::MembersOf(v.clone()),
+                other => return Err(format!("Expected class name, got {:?}", other)),
+            }
+        }
+        "dynamic" => {
+            expect_word(it, "bootp", word)?;
+            expect_word(it, "clients", "bootp")?;
+            PermitKind::DynamicBootpClients
+        }
+        "authenticated" => {
+            expect_word(it, "clients", word)?;
+            PermitKind::AuthenticatedClients
+        }
+        "unauthenticated" => {
+            expect_word(it, "clients", word)?;
+            PermitKind::UnauthenticatedClients
+        }
+        "all" => {
+            expect_word(it, "clients", word)?;
+            PermitKind::AllClients
+        }
+        _ => PermitKind::After(parse_date(it, "after")?),
+    };
+    expect_semicolon(it, "permit")?;
+
+    Ok(Permit { allow, kind })
+}
+
+pub(crate) fn parse_pool(it: &mut Cursor, ctx: &mut Context) -> Result<Pool, String> {
+    it.next(); // "pool"
+    expect_open_brace(it, "pool")?;
+    let mut pool = Pool {
+        failover_peer: None,
+        permits: Vec::new(),
+        statements: Vec::new(),
+    };
+    ctx.scope.push(ScopeEntry::Pool);
+    loop {
+        match it.peek().copied() {
+            None => return Err("Unexpected EOF inside pool block".to_owned()),
+            Some(LexItem::Paren('}')) => {
+                it.next();
+                ctx.scope.pop();
+                return Ok(pool);
+            }
+            Some(_) if is_permit(it) => pool.permits.push(parse_permit(it)?),
+            Some(LexItem::Word(w)) if w.as_str() == "failover" => {
+                let name = parse_failover_peer_name(it)?;
+                // Without a loader, the peer may be declared in an include
+                // that isn't followed.
+                if ctx.loader.is_some() && !ctx.failover_peers.contains(&name) {
+                    return Err(format!("Unknown failover peer {:?}", name));
+                }
+                expect_semicolon(it, "failover peer name")?;
+                pool.failover_peer = Some(name);
+            }
+            Some(_) => {
+                if let Some(statement) = parse_statement(it, ctx)? {
+                    pool.statements.push(statement);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_range(it: &mut Cursor) -> Result<Range, String> {
+    it.next(); // "range"
+    let dynamic_bootp = match it.peek().copied() {
+        Some(LexItem::Word(w)) if w.as_str() == "dynamic-bootp" => {
+            it.next();
+            true
+        }
+        _ => false,
+    };
+    let low = parse_ipv4(it, "range start")?;
+    let high = match it.peek().copied() {
+        Some(LexItem::Endl) => low,
+        _ => parse_ipv4(it, "range end")?,
+    };
+    match it.next() {
+        Some(LexItem::Endl) => {}
+        other => return Err(format!("Expected ';' after range, got {:?}", other)),
+    }
+    if low > high {
+        return Err(format!("Range start {} is above its end {}", low, high));
+    }
+
+    Ok(Range {
+        dynamic_bootp,
+        low,
+        high,
+    })
+}
+
+pub(crate) fn parse_range6(it: &mut Cursor) -> Result<Range6, String> {
+    it.next(); // "range6"
+    let (low, high) = match it.next() {
+        Some(LexItem::Word(w)) if w.contains('/') => {
+            let (network, prefix_len) = parse_prefix(w)?;
+            let mask = prefix_mask6(prefix_len);
+            let low = u128::from(network) & mask;
+            (Ipv6Addr::from(low), Ipv6Addr::from(low | !mask))
+        }
+        Some(LexItem::Word(w)) => {
+            let low = w
+                .parse::<Ipv6Addr>()
+                .map_err(|_| format!("Invalid range6 start {:?}", w))?;
+            match it.peek().copied() {
+                Some(LexItem::Word(w)) if w.as_str() != "temporary" => {
+                    (low, parse_ipv6(it, "range6 end")?)
+                }
+                _ => (low, low),
+            }
+        }
+        other => return Err(format!("Expected range6 start, got {:?}", other)),
+    };
+    let temporary = match it.peek().copied() {
+        Some(LexItem::Word(w)) if w.as_str() == "temporary" => {
+            it.next();
+            true
+        }
+        _ => false,
+    };
+    expect_semicolon(it, "range6")?;
+    if low > high {
+        return Err(format!("Range6 start {} is above its end {}", low, high));
+    }
+
+    Ok(Range6 {
+        low,
+        high,
+        temporary,
+    })
+}
+
+pub(crate) fn parse_prefix6(it: &mut Cursor) -> Result<Prefix6, String> {
+    it.next(); // "prefix6"
+    let low = parse_ipv6(it, "prefix6 start")?;
+    let high = parse_ipv6(it, "prefix6 end")?;
+    let prefix_len = match it.next() {
+        Some(LexItem::Word(w)) => match w.strip_prefix('/').map(|l| l.parse::<u8>()) {
+            Some(Ok(l)) if l <= 128 => l,
+            _ => return Err(format!("Invalid prefix6 length {:?}", w)),
+        },
+        other => return Err(format!("Expected prefix6 length, got {:?}", other)),
+    };
+    expect_semicolon(it, "prefix6")?;
+    if low > high {
+        return Err(format!("Prefix6 start {} is above its end {}", low, high));
+    }
+
+    Ok(Prefix6 {
+        low,
+        high,
+        prefix_len,
+    })
+}
+
+/// Collect the raw tokens of a statement up to and including its `;`. Stops
+/// in front of a `}` closing the enclosing block, or of a `{`.
+fn parse_args(it: &mut Cursor) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    loop {
+        match it.peek().copied() {
+            None | Some(LexItem::Paren('}')) | Some(LexItem::Paren('{')) => return Ok(args),
+            Some(LexItem::Endl) => {
+                it.next();
+                return Ok(args);
+            }
+            Some(t) => {
+                args.push(t.raw());
+                it.next();
+            }
+        }
+    }
+}
+
+/// Parse an `if` statement starting at its keyword, including the `}` of
+/// the last branch.
+pub(crate) fn parse_if(it: &mut Cursor, ctx: &mut Context) -> Result<If, String> {
+    it.next(); // "if"
+    let mut branches = Vec::new();
+    let mut else_statements = None;
+    loop {
+        let condition = parse_expression(it)?;
+        expect_open_brace(it, "condition")?;
+        branches.push(IfBranch {
+            condition,
+            statements: parse_declarations(it, ctx, true)?,
+        });
+
+        match it.peek() {
+            Some(LexItem::Word(w)) if w == "elsif" => {
+                it.next();
+            }
+            Some(LexItem::Word(w)) if w == "else" => {
+                it.next();
+                match it.peek() {
+                    Some(LexItem::Word(w)) if w == "if" => {
+                        it.next();
+                    }
+                    _ => {
+                        expect_open_brace(it, "else")?;
+                        else_statements = Some(parse_declarations(it, ctx, true)?);
+                        break;
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+
+    Ok(If {
+        branches,
+        else_statements,
+    })
+}
+
+/// Parse an `on` block starting at its keyword, including the closing `}`.
+pub(crate) fn parse_on(it: &mut Cursor, ctx: &mut Context) -> Result<On, String> {
+    it.next(); // "on"
+    let mut events = Vec::new();
+    loop {
+        match it.next() {
+            Some(LexItem::Word(w)) => events.push(OnEvent::from(w)?),
+            other => return Err(format!("Expected event name, got {:?}", other)),
+        }
+        match it.peek() {
+            Some(LexItem::Word(w)) if w == "or" => {
+                it.next();
+            }
+            _ => break,
+        }
+    }
+    expect_open_brace(it, "event names")?;
+    let statements = parse_declarations(it, ctx, true)?;
+
+    Ok(On { events, statements })
+}
+
+/// Parse a `set` statement starting at its keyword, including the `;`.
+pub(crate) fn parse_set(it: &mut Cursor) -> Result<Set, String> {
+    it.next(); // "set"
+    let name = match it.next() {
+        Some(LexItem::Word(w)) => w.clone(),
+        other => {
+            return Err(format!(
+                "Expected variable name after 'set', got {:?}",
+                other
+            ))
+        }
+    };
+    match it.next() {
+        Some(LexItem::Word(w)) if w == "=" => {}
+        other => {
+            return Err(format!(
+                "Expected '=' after 'set {}', got {:?}",
+                name, other
+            ))
+        }
+    }
+    let value = parse_expression(it)?;
+    expect_semicolon(it, "set")?;
+
+    Ok(Set { name, value })
+}
+
+/// Parse an `unset` statement starting at its keyword, including the `;`.
+pub(crate) fn parse_unset(it: &mut Cursor) -> Result<String, String> {
+    it.next(); // "unset"
+    let name = match it.next() {
+        Some(LexItem::Word(w)) => w.clone(),
+        other => {
+            return Err(format!(
+                "Expected variable name after 'unset', got {:?}",
+                other
+            ))
+        }
+    };
+    expect_semicolon(it, "unset")?;
+
+    Ok(name)
+}
+
+/// Parse a simple statement not modeled otherwise as a generic
+/// [`Parameter`].
+pub(crate) fn parse_parameter(it: &mut Cursor) -> Result<Parameter, String> {
+    let name = match it.next() {
+        Some(t) => t.to_string(),
+        None => return Err("Statement expected".to_owned()),
+    };
+
+    Ok(Parameter {
+        name,
+        args: parse_args(it)?,
+    })
+}
@@ -0,0 +1,471 @@
+use std::fmt;
+
+use crate::common::Span;
+use crate::config::Config;
+use crate::config::Statement;
+use crate::lex::lex;
+use crate::lex::LexItem;
+use crate::lex::Token;
+use crate::parser::parse_lexed;
+use crate::parser::ParserOptions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs and line breaks
+    Whitespace,
+    /// A `#` comment, up to but excluding the line break
+    Comment,
+}
+
+/// Text between tokens, which the parser skips but the syntax tree keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// A token as written, together with the trivia preceding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub leading: Vec<Trivia>,
+    /// The token as written, e.g. with the quotes and escapes of a string.
+    pub text: String,
+    pub span: Span,
+}
+
+/// `{`, the statements inside and the matching `}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxBlock {
+    pub open: SyntaxToken,
+    pub statements: Vec<SyntaxNode>,
+    pub close: SyntaxToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Token(SyntaxToken),
+    Block(SyntaxBlock),
+}
+
+/// A statement: its tokens up to the terminating `;`, or up to the end of
+/// the block it opens. An `if` statement includes its `elsif` and `else`
+/// branches, a `;` following a block belongs to the statement.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub elements: Vec<SyntaxElement>,
+}
+
+/// Lossless syntax tree of a configuration file: printing it gives back the
+/// input byte for byte, comments and formatting included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxTree {
+    pub statements: Vec<SyntaxNode>,
+    /// Trivia after the last token
+    pub trailing: Vec<Trivia>,
+}
+
+impl SyntaxNode {
+    fn first_token(&self) -> &SyntaxToken {
+        match &self.elements[0] {
+            SyntaxElement::Token(t) => t,
+            SyntaxElement::Block(b) => &b.open,
+        }
+    }
+
+    fn last_token(&self) -> &SyntaxToken {
+        match &self.elements[self.elements.len() - 1] {
+            SyntaxElement::Token(t) => t,
+            SyntaxElement::Block(b) => &b.close,
+        }
+    }
+
+    /// Span from the first to the last token of the statement, excluding
+    /// the trivia before it.
+    pub fn span(&self) -> Span {
+        Span {
+            end: self.last_token().span.end,
+            ..self.first_token().span.clone()
+        }
+    }
+
+    /// The first token, e.g. `subnet` or `option`.
+    pub fn keyword(&self) -> &str {
+        &self.first_token().text
+    }
+
+    /// Tokens of the statement outside its blocks.
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        self.elements
+            .iter()
+            .filter_map(|e| match e {
+                SyntaxElement::Token(t) => Some(t),
+                SyntaxElement::Block(_) => None,
+            })
+            .collect()
+    }
+
+    /// Statements nested inside the blocks of this statement, in order.
+    pub fn children(&self) -> Vec<&SyntaxNode> {
+        self.elements
+            .iter()
+            .filter_map(|e| match e {
+                SyntaxElement::Block(b) => Some(&b.statements),
+                SyntaxElement::Token(_) => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// The statement starting at byte offset `start`, looking at this
+    /// statement and the ones nested inside.
+    fn find(&self, start: usize) -> Option<&SyntaxNode> {
+        let span = self.span();
+        if span.start == start {
+            return Some(self);
+        }
+        if start < span.start || start >= span.end {
+            return None;
+        }
+        self.children().into_iter().find_map(|c| c.find(start))
+    }
+}
+
+impl SyntaxTree {
+    /// The statement starting at byte offset `start`, at any nesting level.
+    pub fn find(&self, start: usize) -> Option<&SyntaxNode> {
+        self.statements.iter().find_map(|s| s.find(start))
+    }
+}
+
+fn write_trivia(f: &mut fmt::Formatter, trivia: &[Trivia]) -> fmt::Result {
+    for t in trivia {
+        write!(f, "{}", t.text)?;
+    }
+
+    Ok(())
+}
+
+/// Keeps the value of `secret` statements out of logs, like
+/// [`Secret`](crate::ddns::Secret) does.
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = f.debug_struct("SyntaxNode");
+        if self.keyword() == "secret" {
+            s.field(
+                "elements",
+                &format_args!("[{:?}, <redacted>]", self.first_token()),
+            );
+        } else {
+            s.field("elements", &self.elements);
+        }
+        s.finish()
+    }
+}
+
+impl fmt::Display for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_trivia(f, &self.leading)?;
+        write!(f, "{}", self.text)
+    }
+}
+
+impl fmt::Display for SyntaxBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.open)?;
+        for s in &self.statements {
+            write!(f, "{}", s)?;
+        }
+        write!(f, "{}", self.close)
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for e in &self.elements {
+            match e {
+                SyntaxElement::Token(t) => write!(f, "{}", t)?,
+                SyntaxElement::Block(b) => write!(f, "{}", b)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for s in &self.statements {
+            write!(f, "{}", s)?;
+        }
+        write_trivia(f, &self.trailing)
+    }
+}
+
+/// Split the text between two tokens into whitespace and comments.
+fn trivia(text: &str) -> Vec<Trivia> {
+    let mut result = Vec::new();
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (kind, len) = if rest.starts_with('#') {
+            (TriviaKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else {
+            (TriviaKind::Whitespace, rest.find('#').unwrap_or(rest.len()))
+        };
+        result.push(Trivia {
+            kind,
+            text: rest[..len].to_owned(),
+        });
+        rest = &rest[len..];
+    }
+
+    result
+}
+
+/// Groups lexed tokens into statements, attaching the text between them.
+struct Builder<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+    /// End of the last token taken
+    prev_end: usize,
+}
+
+impl<'a> Builder<'a> {
+    fn peek(&self) -> Option<&'a LexItem> {
+        self.tokens.get(self.pos).map(|t| &t.item)
+    }
+
+    fn token(&mut self) -> SyntaxToken {
+        let span = self.tokens[self.pos].span.clone();
+        let leading = trivia(&self.source[self.prev_end..span.start]);
+        self.pos += 1;
+        self.prev_end = span.end;
+
+        SyntaxToken {
+            leading,
+            text: self.source[span.start..span.end].to_owned(),
+            span,
+        }
+    }
+
+    fn block(&mut self) -> Result<SyntaxBlock, String> {
+        let open = self.token();
+        let mut statements = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(format!("{}: Unexpected EOF: unclosed '{{'", open.span)),
+                Some(LexItem::Paren('}')) => {
+                    return Ok(SyntaxBlock {
+                        open,
+                        statements,
+                        close: self.token(),
+                    })
+                }
+                Some(_) => statements.push(self.node()?),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<SyntaxNode, String> {
+        let mut elements = Vec::new();
+        loop {
+            match self.peek() {
+                None => break,
+                Some(LexItem::Endl) => {
+                    elements.push(SyntaxElement::Token(self.token()));
+                    break;
+                }
+                // Either a stray `}` at the top level, or the end of the
+                // block an unterminated statement is in.
+                Some(LexItem::Paren('}')) => {
+                    if elements.is_empty() {
+                        elements.push(SyntaxElement::Token(self.token()));
+                    }
+                    break;
+                }
+                Some(LexItem::Paren('{')) => {
+                    elements.push(SyntaxElement::Block(self.block()?));
+                    match self.peek() {
+                        Some(LexItem::Word(w)) if w == "elsif" || w == "else" => {}
+                        Some(LexItem::Endl) => {
+                            elements.push(SyntaxElement::Token(self.token()));
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+                Some(_) => elements.push(SyntaxElement::Token(self.token())),
+            }
+        }
+
+        Ok(SyntaxNode { elements })
+    }
+}
+
+fn build(source: &str, tokens: &[Token]) -> Result<SyntaxTree, String> {
+    let mut builder = Builder {
+        source,
+        tokens,
+        pos: 0,
+        prev_end: 0,
+    };
+    let mut statements = Vec::new();
+    while builder.peek().is_some() {
+        statements.push(builder.node()?);
+    }
+
+    Ok(SyntaxTree {
+        statements,
+        trailing: trivia(&source[builder.prev_end..]),
+    })
+}
+
+/// Replacement of the text between two byte offsets of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl TextEdit {
+    /// Replace the text covered by `span`.
+    pub fn replace<S: Into<String>>(span: &Span, text: S) -> TextEdit {
+        TextEdit {
+            start: span.start,
+            end: span.end,
+            text: text.into(),
+        }
+    }
+
+    /// Insert `text` at byte offset `at`.
+    pub fn insert<S: Into<String>>(at: usize, text: S) -> TextEdit {
+        TextEdit {
+            start: at,
+            end: at,
+            text: text.into(),
+        }
+    }
+}
+
+/// A configuration file kept as written, with both its lossless syntax tree
+/// and its typed syntax tree. Edits are made to the text, so that everything
+/// outside of them, comments included, stays untouched.
+#[derive(Clone)]
+pub struct Document {
+    source: String,
+    options: ParserOptions,
+    syntax: SyntaxTree,
+    config: Config,
+}
+
+impl Document {
+    /// The text of the document, as written and edited.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn syntax(&self) -> &SyntaxTree {
+        &self.syntax
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The node of the syntax tree a typed statement was parsed from.
+    pub fn node(&self, statement: &Statement) -> Option<&SyntaxNode> {
+        self.syntax.find(statement.span.start)
+    }
+
+    /// Apply an edit to the text and parse the result. The document is left
+    /// unchanged when the edited text fails to parse.
+    pub fn apply(&mut self, edit: &TextEdit) -> Result<(), String> {
+        self.apply_all(std::slice::from_ref(edit))
+    }
+
+    /// Apply edits that don't overlap, with ranges referring to the current
+    /// text, and parse the result once. Edits inserting at the same offset
+    /// are applied in the order given.
+    pub fn apply_all(&mut self, edits: &[TextEdit]) -> Result<(), String> {
+        let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+        sorted.sort_by_key(|e| (e.start, e.end));
+
+        let mut source = String::with_capacity(self.source.len());
+        let mut pos = 0;
+        for edit in sorted {
+            if edit.start > edit.end
+                || !self.source.is_char_boundary(edit.start)
+                || !self.source.is_char_boundary(edit.end)
+            {
+                return Err(format!(
+                    "Invalid edit range {}..{} for a document of {} bytes",
+                    edit.start,
+                    edit.end,
+                    self.source.len()
+                ));
+            }
+            if edit.start < pos {
+                return Err(format!(
+                    "Edit of {}..{} overlaps another edit",
+                    edit.start, edit.end
+                ));
+            }
+            source.push_str(&self.source[pos..edit.start]);
+            source.push_str(&edit.text);
+            pos = edit.end;
+        }
+        source.push_str(&self.source[pos..]);
+
+        *self = parse_document_with(source, &self.options)?;
+        Ok(())
+    }
+}
+
+/// Leaves out the source, which contains key secrets, in favor of the
+/// redacted syntax tree.
+impl fmt::Debug for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Document")
+            .field("source", &format_args!("<{} bytes>", self.source.len()))
+            .field("options", &self.options)
+            .field("syntax", &self.syntax)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Parse a configuration file into a [`Document`] for editing. `include`
+/// statements are recorded but not followed.
+pub fn parse_document<S>(input: S) -> Result<Document, String>
+where
+    S: Into<String>,
+{
+    parse_document_with(input, &ParserOptions::default())
+}
+
+/// Like [`parse_document`], with the strictness and limits of `options`.
+pub fn parse_document_with<S>(input: S, options: &ParserOptions) -> Result<Document, String>
+where
+    S: Into<String>,
+{
+    let source = input.into();
+    let tokens = lex(source.as_str()).map_err(|err| format!("Lexer error: {err}"))?;
+    // The typed parser bounds the nesting the syntax tree is built with.
+    let config = parse_lexed(&tokens, options)?;
+    let syntax = build(&source, &tokens)?;
+
+    Ok(Document {
+        source,
+        options: options.clone(),
+        syntax,
+        config,
+    })
+}
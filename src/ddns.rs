@@ -0,0 +1,148 @@
+use std::fmt;
+
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+
+/// A secret value, redacted when formatted so that parsed configurations can
+/// be logged safely. Use [`Secret::expose`] to get the value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new<S: Into<String>>(secret: S) -> Secret {
+        Secret(secret.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Secret(<redacted>)")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+/// `key name { algorithm hmac-md5; secret "..."; }`: a TSIG key used for
+/// DNS updates and OMAPI.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TsigKey {
+    pub name: String,
+    pub algorithm: Option<String>,
+    pub secret: Option<Secret>,
+}
+
+/// `zone example.com. { primary 127.0.0.1; key name; }`: where DNS updates
+/// for a zone are sent.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DdnsZone {
+    pub name: String,
+    pub primary: Option<String>,
+    pub primary6: Option<String>,
+    pub secondary: Option<String>,
+    pub secondary6: Option<String>,
+    /// Name of the [`TsigKey`] signing the updates.
+    pub key: Option<String>,
+}
+
+fn parse_name(it: &mut Cursor, what: &str) -> Result<String, String> {
+    match it.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => Ok(w.clone()),
+        other => Err(format!("Expected {}, got {:?}", what, other)),
+    }
+}
+
+fn expect_open_brace(it: &mut Cursor, after: &str) -> Result<(), String> {
+    match it.next() {
+        Some(LexItem::Paren('{')) => Ok(()),
+        other => Err(format!("Expected '{{' after {}, got {:?}", after, other)),
+    }
+}
+
+/// Parse a key secret. The lexer ends words at `=`, so the padding of an
+/// unquoted base64 secret follows as separate tokens.
+fn parse_secret(it: &mut Cursor) -> Result<String, String> {
+    let mut secret = parse_name(it, "key secret")?;
+    while let Some(LexItem::Word(w)) = it.peek().copied() {
+        if w.as_str() != "=" {
+            break;
+        }
+        secret.push('=');
+        it.next();
+    }
+    Ok(secret)
+}
+
+/// Parse a `key` declaration starting at its keyword, including the closing
+/// `}` and an optional `;` following it.
+pub(crate) fn parse_key(it: &mut Cursor) -> Result<TsigKey, String> {
+    it.next(); // "key"
+    let mut key = TsigKey {
+        name: parse_name(it, "key name")?,
+        ..TsigKey::default()
+    };
+    expect_open_brace(it, "key name")?;
+
+    loop {
+        match it.next() {
+            None => return Err("Unexpected EOF inside key block".to_owned()),
+            Some(LexItem::Paren('}')) => break,
+            Some(LexItem::Word(w)) if w.as_str() == "algorithm" => {
+                key.algorithm = Some(parse_name(it, "key algorithm")?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "secret" => {
+                key.secret = Some(Secret::new(parse_secret(it)?));
+            }
+            other => return Err(format!("Unexpected {:?} in key block", other)),
+        }
+        if it.next() != Some(&LexItem::Endl) {
+            return Err("Semicolon expected inside key block".to_owned());
+        }
+    }
+    if it.peek() == Some(&&LexItem::Endl) {
+        it.next();
+    }
+
+    Ok(key)
+}
+
+/// Parse a `zone` declaration starting at its keyword, including the closing
+/// `}`. The key is resolved lazily by [`DdnsZone::tsig_key`], as it is
+/// commonly declared in an included file.
+pub(crate) fn parse_zone(it: &mut Cursor) -> Result<DdnsZone, String> {
+    it.next(); // "zone"
+    let mut zone = DdnsZone {
+        name: parse_name(it, "zone name")?,
+        ..DdnsZone::default()
+    };
+    expect_open_brace(it, "zone name")?;
+
+    loop {
+        let word = match it.next() {
+            None => return Err("Unexpected EOF inside zone block".to_owned()),
+            Some(LexItem::Paren('}')) => break,
+            Some(LexItem::Word(w)) => w.as_str(),
+            Some(other) => return Err(format!("Unexpected {:?} in zone block", other)),
+        };
+        match word {
+            "primary" => zone.primary = Some(parse_name(it, "primary server")?),
+            "primary6" => zone.primary6 = Some(parse_name(it, "primary6 server")?),
+            "secondary" => zone.secondary = Some(parse_name(it, "secondary server")?),
+            "secondary6" => zone.secondary6 = Some(parse_name(it, "secondary6 server")?),
+            "key" => zone.key = Some(parse_name(it, "key name")?),
+            _ => return Err(format!("Unknown zone statement {:?}", word)),
+        }
+        if it.next() != Some(&LexItem::Endl) {
+            return Err("Semicolon expected inside zone block".to_owned());
+        }
+    }
+
+    Ok(zone)
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// The dhcpd implementation whose grammar an input follows. OpenBSD dhcpd
+/// accepts a subset of ISC dhcpd's statements and writes a few lease
+/// entries differently, e.g. `abandoned;` instead of
+/// `binding state abandoned;`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    OpenBsd,
+    Isc,
+    /// Detect the dialect from the first construct specific to one of them.
+    #[default]
+    Auto,
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = match self {
+            Dialect::OpenBsd => "OpenBSD",
+            Dialect::Isc => "ISC",
+            Dialect::Auto => "auto",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl Dialect {
+    pub fn from(s: &str) -> Result<Dialect, String> {
+        match s {
+            "openbsd" | "OpenBSD" => Ok(Dialect::OpenBsd),
+            "isc" | "ISC" => Ok(Dialect::Isc),
+            "auto" => Ok(Dialect::Auto),
+            _ => Err(format!("'{}' is not a recognized dialect", s)),
+        }
+    }
+}
+
+/// Dialect requested for one input, and the one detected so far.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DialectState {
+    pub(crate) dialect: Dialect,
+    /// Reject constructs the dialect in effect doesn't accept.
+    pub(crate) strict: bool,
+    pub(crate) detected: Option<Dialect>,
+}
+
+impl DialectState {
+    pub(crate) fn new(dialect: Dialect, strict: bool) -> DialectState {
+        DialectState {
+            dialect,
+            strict,
+            detected: None,
+        }
+    }
+
+    /// The dialect in effect: the requested one, or the detected one when
+    /// auto-detecting.
+    pub(crate) fn resolved(&self) -> Option<Dialect> {
+        match self.dialect {
+            Dialect::Auto => self.detected,
+            d => Some(d),
+        }
+    }
+
+    /// Record a construct only `dialect` accepts, described by `what`. In
+    /// strict mode, fails when another dialect is in effect.
+    pub(crate) fn construct(&mut self, dialect: Dialect, what: &str) -> Result<(), String> {
+        match self.resolved() {
+            None => self.detected = Some(dialect),
+            Some(d) if d != dialect && self.strict => {
+                return Err(format!("{} is not supported by {} dhcpd", what, d));
+            }
+            Some(_) => {}
+        }
+
+        Ok(())
+    }
+}
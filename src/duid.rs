@@ -0,0 +1,145 @@
+use std::fmt;
+
+use crate::common::decode_hex;
+
+/// A DHCP unique identifier (RFC 8415, section 11).
+///
+/// Found in `server-duid` entries and inside the identifiers of DHCPv6
+/// identity associations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Duid {
+    /// DUID-LLT: link-layer address plus time (type 1)
+    Llt {
+        hardware_type: u16,
+        /// Seconds since midnight (UTC), January 1, 2000, modulo 2^32
+        time: u32,
+        link_layer_address: Vec<u8>,
+    },
+    /// DUID-EN: assigned by vendor based on enterprise number (type 2)
+    En {
+        enterprise_number: u32,
+        identifier: Vec<u8>,
+    },
+    /// DUID-LL: link-layer address (type 3)
+    Ll {
+        hardware_type: u16,
+        link_layer_address: Vec<u8>,
+    },
+    /// DUID-UUID: universally unique identifier (type 4)
+    Uuid([u8; 16]),
+    /// A DUID of a type not defined above, or one too short for its type.
+    Unknown(Vec<u8>),
+}
+
+/// Hardware type of Ethernet in the IANA ARP parameters registry.
+const HTYPE_ETHERNET: u16 = 1;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+impl Duid {
+    /// Decode a DUID from its wire representation.
+    pub fn from_bytes(bytes: &[u8]) -> Duid {
+        if bytes.len() < 2 {
+            return Duid::Unknown(bytes.to_vec());
+        }
+        let duid_type = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let data = &bytes[2..];
+
+        match duid_type {
+            1 if data.len() >= 6 => Duid::Llt {
+                hardware_type: u16::from_be_bytes([data[0], data[1]]),
+                time: u32::from_be_bytes([data[2], data[3], data[4], data[5]]),
+                link_layer_address: data[6..].to_vec(),
+            },
+            2 if data.len() >= 4 => Duid::En {
+                enterprise_number: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+                identifier: data[4..].to_vec(),
+            },
+            3 if data.len() >= 2 => Duid::Ll {
+                hardware_type: u16::from_be_bytes([data[0], data[1]]),
+                link_layer_address: data[2..].to_vec(),
+            },
+            4 if data.len() == 16 => {
+                let mut uuid = [0u8; 16];
+                uuid.copy_from_slice(data);
+                Duid::Uuid(uuid)
+            }
+            _ => Duid::Unknown(bytes.to_vec()),
+        }
+    }
+
+    /// Parse a DUID written in colon-separated hex form, e.g. `00:01:00:01:...`.
+    pub fn from_hex<S: AsRef<str>>(input: S) -> Result<Duid, String> {
+        Ok(Duid::from_bytes(&decode_hex(input)?))
+    }
+
+    /// Encode the DUID into its wire representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        match self {
+            Duid::Llt {
+                hardware_type,
+                time,
+                link_layer_address,
+            } => {
+                result.extend_from_slice(&1u16.to_be_bytes());
+                result.extend_from_slice(&hardware_type.to_be_bytes());
+                result.extend_from_slice(&time.to_be_bytes());
+                result.extend_from_slice(link_layer_address);
+            }
+            Duid::En {
+                enterprise_number,
+                identifier,
+            } => {
+                result.extend_from_slice(&2u16.to_be_bytes());
+                result.extend_from_slice(&enterprise_number.to_be_bytes());
+                result.extend_from_slice(identifier);
+            }
+            Duid::Ll {
+                hardware_type,
+                link_layer_address,
+            } => {
+                result.extend_from_slice(&3u16.to_be_bytes());
+                result.extend_from_slice(&hardware_type.to_be_bytes());
+                result.extend_from_slice(link_layer_address);
+            }
+            Duid::Uuid(uuid) => {
+                result.extend_from_slice(&4u16.to_be_bytes());
+                result.extend_from_slice(uuid);
+            }
+            Duid::Unknown(bytes) => result.extend_from_slice(bytes),
+        }
+        result
+    }
+
+    /// MAC address embedded in a DUID-LLT or DUID-LL of an Ethernet interface,
+    /// in the lowercase colon-separated form used for `hardware ethernet`.
+    pub fn mac(&self) -> Option<String> {
+        match self {
+            Duid::Llt {
+                hardware_type,
+                link_layer_address,
+                ..
+            }
+            | Duid::Ll {
+                hardware_type,
+                link_layer_address,
+            } if *hardware_type == HTYPE_ETHERNET && link_layer_address.len() == 6 => {
+                Some(hex(link_layer_address))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Duid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", hex(&self.to_bytes()))
+    }
+}
@@ -0,0 +1,438 @@
+use std::net::Ipv4Addr;
+
+use crate::common::decode_hex;
+use crate::common::Span;
+use crate::config::Config;
+use crate::config::ScopePath;
+use crate::config::Statement;
+use crate::config::StatementKind;
+use crate::cst::Document;
+use crate::cst::SyntaxElement;
+use crate::cst::SyntaxNode;
+use crate::cst::TextEdit;
+use crate::parser::Host;
+use crate::resolve::scope_of;
+use crate::writer::write_host;
+
+/// Host declarations at any nesting level.
+fn host_statements<'a>(statements: &'a [Statement], result: &mut Vec<(&'a Statement, &'a Host)>) {
+    for statement in statements {
+        if let StatementKind::Host(host) = &statement.kind {
+            result.push((statement, host));
+        }
+        host_statements(statement.children(), result);
+    }
+}
+
+fn find_host<'a>(config: &'a Config, name: &str) -> Result<(&'a Statement, &'a Host), String> {
+    let mut hosts = Vec::new();
+    host_statements(&config.statements, &mut hosts);
+    hosts
+        .into_iter()
+        .find(|(_, h)| h.name == name)
+        .ok_or_else(|| format!("Unknown host {:?}", name))
+}
+
+/// The declaration of the scope at `path`, `None` for the global scope.
+fn find_scope<'a>(config: &'a Config, path: &ScopePath) -> Result<Option<&'a Statement>, String> {
+    let mut statements: &[Statement] = &config.statements;
+    let mut found = None;
+    for entry in &path.entries {
+        let mut matches = statements
+            .iter()
+            .filter(|s| matches!(scope_of(s), Some((e, _)) if &e == entry));
+        let statement = match matches.next() {
+            Some(s) => s,
+            None => return Err(format!("Unknown scope {:?}", path.to_string())),
+        };
+        if matches.next().is_some() {
+            return Err(format!("Scope {:?} is ambiguous", path.to_string()));
+        }
+        statements = statement.children();
+        found = Some(statement);
+    }
+
+    Ok(found)
+}
+
+/// The node of the scope declaration at `path`, `None` for the global scope.
+fn scope_node<'a>(
+    document: &'a Document,
+    path: &ScopePath,
+) -> Result<Option<&'a SyntaxNode>, String> {
+    match find_scope(document.config(), path)? {
+        Some(statement) => match document.node(statement) {
+            Some(node) => Ok(Some(node)),
+            None => Err(format!("No syntax node for scope {:?}", path.to_string())),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Check that `host` doesn't reuse the name, MAC address or fixed
+/// addresses of another host than the one named `except`.
+fn check_unique(config: &Config, host: &Host, except: Option<&str>) -> Result<(), String> {
+    let mut hosts = Vec::new();
+    host_statements(&config.statements, &mut hosts);
+
+    for (_, other) in hosts {
+        if Some(other.name.as_str()) == except {
+            continue;
+        }
+        if other.name == host.name {
+            return Err(format!("Host {:?} already exists", host.name));
+        }
+        if let (Some(mac), Some(other_mac)) = (&host.mac, &other.mac) {
+            if mac.to_lowercase() == *other_mac {
+                return Err(format!(
+                    "MAC address {} is already used by host {:?}",
+                    mac, other.name
+                ));
+            }
+        }
+        if let Some(a) = host
+            .fixed_addresses
+            .iter()
+            .find(|a| other.fixed_addresses.contains(a))
+        {
+            return Err(format!(
+                "Address {} is already used by host {:?}",
+                a, other.name
+            ));
+        }
+        if let Some(a) = host
+            .fixed_addresses6
+            .iter()
+            .find(|a| other.fixed_addresses6.contains(a))
+        {
+            return Err(format!(
+                "Address {} is already used by host {:?}",
+                a, other.name
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_mac(mac: &str) -> Result<(), String> {
+    match decode_hex(mac) {
+        Ok(bytes) if !bytes.is_empty() => Ok(()),
+        _ => Err(format!("Invalid MAC address {:?}", mac)),
+    }
+}
+
+/// Check that a host declared in `scope` has a fixed address dhcpd can hand
+/// out on the enclosing subnet, if any.
+fn check_subnet(host: &Host, scope: &ScopePath) -> Result<(), String> {
+    let host = Host {
+        scope: scope.clone(),
+        ..host.clone()
+    };
+    // Addresses may also be given as host names, which can't be checked.
+    let addresses = host
+        .fixed_addresses
+        .iter()
+        .all(|a| a.parse::<Ipv4Addr>().is_ok());
+    if let Some((network, prefix_len)) = scope.subnet() {
+        if addresses && !host.fixed_addresses.is_empty() && host.fixed_address_on_subnet().is_none()
+        {
+            return Err(format!(
+                "Host {:?} has no fixed-address on subnet {}/{}",
+                host.name, network, prefix_len
+            ));
+        }
+    }
+    if let Some((network, prefix_len)) = scope.subnet6() {
+        if !host.fixed_addresses6.is_empty() && host.fixed_address6_on_subnet().is_none() {
+            return Err(format!(
+                "Host {:?} has no fixed-address6 on subnet6 {}/{}",
+                host.name, network, prefix_len
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whitespace before `offset` on its line, `None` when other text precedes
+/// it there.
+fn indent_at(source: &str, offset: usize) -> Option<&str> {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &source[line_start..offset];
+    if indent.trim().is_empty() {
+        Some(indent)
+    } else {
+        None
+    }
+}
+
+fn line_ending(source: &str) -> &'static str {
+    if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Offset past `offset` and the rest of its line, excluding the line break,
+/// when that rest holds nothing but whitespace and a comment.
+fn skip_trailing_trivia(source: &str, offset: usize) -> usize {
+    let rest = &source[offset..];
+    let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        offset + line.trim_end_matches('\r').len()
+    } else {
+        offset
+    }
+}
+
+/// Insert a statement as the last one of `scope`, or of the file for the
+/// global scope, indented like the statements around it. `lines` after the
+/// first are indented relative to the statement.
+fn insert_statement(document: &Document, scope: Option<&SyntaxNode>, lines: &[String]) -> TextEdit {
+    let source = document.source();
+    let eol = line_ending(source);
+
+    let (offset, indent, parent_indent) = match scope {
+        None => match document.syntax().statements.last() {
+            Some(last) => {
+                let span = last.span();
+                let indent = indent_at(source, span.start).unwrap_or("");
+                (
+                    skip_trailing_trivia(source, span.end),
+                    indent.to_owned(),
+                    None,
+                )
+            }
+            None => {
+                let mut text = String::new();
+                if !source.is_empty() && !source.ends_with('\n') {
+                    text.push_str(eol);
+                }
+                text.push_str(&lines.join(eol));
+                text.push_str(eol);
+                return TextEdit::insert(source.len(), text);
+            }
+        },
+        Some(node) => {
+            let parent_indent = indent_at(source, node.span().start).unwrap_or("");
+            let unit = if parent_indent.contains('\t') {
+                "\t"
+            } else {
+                "  "
+            };
+            let block = node
+                .elements
+                .iter()
+                .find_map(|e| match e {
+                    SyntaxElement::Block(b) => Some(b),
+                    SyntaxElement::Token(_) => None,
+                })
+                .expect("scope declarations have a block");
+            match block.statements.last() {
+                Some(last) => {
+                    let span = last.span();
+                    let indent = match indent_at(source, span.start) {
+                        Some(indent) => indent.to_owned(),
+                        None => format!("{}{}", parent_indent, unit),
+                    };
+                    let offset = skip_trailing_trivia(source, span.end);
+                    (
+                        offset,
+                        indent,
+                        Some((parent_indent, block.close.span.start)),
+                    )
+                }
+                None => {
+                    let offset = skip_trailing_trivia(source, block.open.span.end);
+                    (
+                        offset,
+                        format!("{}{}", parent_indent, unit),
+                        Some((parent_indent, block.close.span.start)),
+                    )
+                }
+            }
+        }
+    };
+
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(eol);
+        if !line.is_empty() {
+            text.push_str(&indent);
+            text.push_str(line);
+        }
+    }
+    // Keep the closing brace of the scope on a line of its own.
+    if let Some((parent_indent, close)) = parent_indent {
+        if !source[offset..close].contains('\n') {
+            text.push_str(eol);
+            text.push_str(parent_indent);
+        }
+    }
+
+    TextEdit::insert(offset, text)
+}
+
+/// Start of the comment lines directly above the line starting at
+/// `line_start`, up to the first blank line.
+fn leading_comments(source: &str, line_start: usize) -> usize {
+    let mut start = line_start;
+    while start > 0 {
+        let prev = source[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+        if !source[prev..start].trim_start().starts_with('#') {
+            break;
+        }
+        start = prev;
+    }
+    start
+}
+
+/// Range of a statement together with the comments documenting it: the
+/// comment lines directly above it when it starts its line, and a comment
+/// following it on the same line. The range covers whole lines when
+/// nothing else is on them.
+fn statement_range(source: &str, span: &Span) -> (usize, usize) {
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let rest = &source[span.end..];
+    let line_end = rest.find('\n').map_or(source.len(), |i| span.end + i + 1);
+    let trailing = source[span.end..line_end].trim_start();
+
+    if !trailing.is_empty() && !trailing.starts_with('#') {
+        (span.start, span.end)
+    } else if source[line_start..span.start].trim().is_empty() {
+        (leading_comments(source, line_start), line_end)
+    } else {
+        (span.start, skip_trailing_trivia(source, span.end))
+    }
+}
+
+/// Delete a statement along with the comments documenting it.
+fn delete_statement(source: &str, span: &Span) -> TextEdit {
+    let (start, end) = statement_range(source, span);
+    TextEdit {
+        start,
+        end,
+        text: String::new(),
+    }
+}
+
+impl Document {
+    /// Declare `host` as the last statement of `scope`, written the way
+    /// [`write_host`] does. Fails when the name, MAC address or one of the
+    /// fixed addresses is already used by another host, or when the host
+    /// has no fixed address on the subnet of `scope`.
+    pub fn add_host(&mut self, scope: &ScopePath, host: &Host) -> Result<Vec<TextEdit>, String> {
+        if host.name.is_empty() {
+            return Err("Host name must not be empty".to_owned());
+        }
+        if let Some(mac) = &host.mac {
+            check_mac(mac)?;
+        }
+        check_unique(self.config(), host, None)?;
+        check_subnet(host, scope)?;
+        let node = scope_node(self, scope)?;
+
+        let text = write_host(host)?;
+        let unit = if self.source().contains("\n\t") {
+            "\t"
+        } else {
+            "  "
+        };
+        let lines: Vec<String> = text
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim_start_matches(' ');
+                let depth = (line.len() - trimmed.len()) / 2;
+                format!("{}{}", unit.repeat(depth), trimmed)
+            })
+            .collect();
+
+        let edits = vec![insert_statement(self, node, &lines)];
+        self.apply_all(&edits)?;
+        Ok(edits)
+    }
+
+    /// Remove the declaration of the host named `name`.
+    pub fn remove_host(&mut self, name: &str) -> Result<Vec<TextEdit>, String> {
+        let (statement, _) = find_host(self.config(), name)?;
+        let node = self
+            .node(statement)
+            .ok_or_else(|| format!("No syntax node for host {:?}", name))?;
+
+        let edits = vec![delete_statement(self.source(), &node.span())];
+        self.apply_all(&edits)?;
+        Ok(edits)
+    }
+
+    /// Change the MAC address of the host named `name`, adding a
+    /// `hardware ethernet` statement if it has none. Fails when another host
+    /// uses the address.
+    pub fn update_host_mac(&mut self, name: &str, mac: &str) -> Result<Vec<TextEdit>, String> {
+        check_mac(mac)?;
+        let (statement, host) = find_host(self.config(), name)?;
+        let updated = Host {
+            mac: Some(mac.to_lowercase()),
+            ..host.clone()
+        };
+        check_unique(self.config(), &updated, Some(name))?;
+        let node = self
+            .node(statement)
+            .ok_or_else(|| format!("No syntax node for host {:?}", name))?;
+
+        let hardware = node
+            .children()
+            .into_iter()
+            .find(|c| c.keyword() == "hardware" && c.tokens().len() == 4);
+        let edit = match hardware {
+            Some(hardware) => TextEdit::replace(&hardware.tokens()[2].span, mac),
+            None => insert_statement(self, Some(node), &[format!("hardware ethernet {};", mac)]),
+        };
+
+        let edits = vec![edit];
+        self.apply_all(&edits)?;
+        Ok(edits)
+    }
+
+    /// Move the declaration of the host named `name` to the end of `scope`,
+    /// keeping its text, comments included. Fails when the host has no fixed
+    /// address on the subnet of `scope`.
+    pub fn move_host(&mut self, name: &str, scope: &ScopePath) -> Result<Vec<TextEdit>, String> {
+        let (statement, host) = find_host(self.config(), name)?;
+        if host.scope == *scope {
+            return Ok(Vec::new());
+        }
+        check_subnet(host, scope)?;
+        let target = scope_node(self, scope)?;
+        let node = self
+            .node(statement)
+            .ok_or_else(|| format!("No syntax node for host {:?}", name))?;
+
+        let source = self.source();
+        let span = node.span();
+        let old_indent = indent_at(source, span.start).unwrap_or("");
+        // Comments documenting the host move along with it.
+        let (start, end) = statement_range(source, &span);
+        let lines: Vec<String> = source[start..end]
+            .trim()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let line = line.trim_end_matches('\r');
+                match i {
+                    0 => line.to_owned(),
+                    _ => line.strip_prefix(old_indent).unwrap_or(line).to_owned(),
+                }
+            })
+            .collect();
+
+        let edits = vec![
+            delete_statement(source, &span),
+            insert_statement(self, target, &lines),
+        ];
+        self.apply_all(&edits)?;
+        Ok(edits)
+    }
+}
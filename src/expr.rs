@@ -0,0 +1,316 @@
+use std::fmt;
+
+use crate::common::decode_hex;
+use crate::common::quote;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+
+/// Binary operators of dhcpd.conf expressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    /// `~=`, case-sensitive regular expression match
+    RegexMatch,
+    /// `~~`, case-insensitive regular expression match
+    IRegexMatch,
+    BinaryOr,
+    BinaryXor,
+    BinaryAnd,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = match self {
+            BinaryOp::Or => "or",
+            BinaryOp::And => "and",
+            BinaryOp::Equal => "=",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::RegexMatch => "~=",
+            BinaryOp::IRegexMatch => "~~",
+            BinaryOp::BinaryOr => "|",
+            BinaryOp::BinaryXor => "^",
+            BinaryOp::BinaryAnd => "&",
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::Remainder => "%",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl BinaryOp {
+    pub fn from(s: &str) -> Result<BinaryOp, String> {
+        match s {
+            "or" => Ok(BinaryOp::Or),
+            "and" => Ok(BinaryOp::And),
+            "=" => Ok(BinaryOp::Equal),
+            "!=" => Ok(BinaryOp::NotEqual),
+            "~=" => Ok(BinaryOp::RegexMatch),
+            "~~" => Ok(BinaryOp::IRegexMatch),
+            "|" => Ok(BinaryOp::BinaryOr),
+            "^" => Ok(BinaryOp::BinaryXor),
+            "&" => Ok(BinaryOp::BinaryAnd),
+            "+" => Ok(BinaryOp::Add),
+            "-" => Ok(BinaryOp::Subtract),
+            "*" => Ok(BinaryOp::Multiply),
+            "/" => Ok(BinaryOp::Divide),
+            "%" => Ok(BinaryOp::Remainder),
+            _ => Err(format!("'{}' is not a recognized operator", s)),
+        }
+    }
+
+    /// Binding strength, higher binds tighter.
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Equal | BinaryOp::NotEqual | BinaryOp::RegexMatch | BinaryOp::IRegexMatch => {
+                3
+            }
+            BinaryOp::BinaryOr => 4,
+            BinaryOp::BinaryXor => 5,
+            BinaryOp::BinaryAnd => 6,
+            BinaryOp::Add | BinaryOp::Subtract => 7,
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Remainder => 8,
+        }
+    }
+}
+
+/// A boolean, data or numeric expression, as used by `if`, `match`, `set`
+/// and computed options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// Quoted string constant
+    Str(String),
+    /// Colon-separated hex constant
+    Data(Vec<u8>),
+    Number(u32),
+    /// `option name`: the value of an option in the client's packet
+    Option(String),
+    /// `config-option name`: the value the server would send
+    ConfigOption(String),
+    /// `exists name`
+    Exists(String),
+    /// A keyword such as `hardware`, `known` or `leased-address`, or a
+    /// variable reference.
+    Name(String),
+    /// Function call, e.g. `substring (option host-name, 0, 3)`.
+    Call {
+        name: String,
+        args: Vec<Expression>,
+    },
+    Not(Box<Expression>),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+}
+
+impl Expression {
+    fn write_operand(&self, f: &mut fmt::Formatter, parent: BinaryOp) -> fmt::Result {
+        match self {
+            Expression::Binary { op, .. } if op.precedence() <= parent.precedence() => {
+                write!(f, "({})", self)
+            }
+            e => write!(f, "{}", e),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Writes the expression in dhcpd.conf syntax.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Str(v) => write!(f, "{}", quote(v)),
+            Expression::Data(v) => write!(
+                f,
+                "{}",
+                v.iter()
+                    .map(|b| format!("{:x}", b))
+                    .collect::<Vec<String>>()
+                    .join(":")
+            ),
+            Expression::Number(v) => write!(f, "{}", v),
+            Expression::Option(v) => write!(f, "option {}", v),
+            Expression::ConfigOption(v) => write!(f, "config-option {}", v),
+            Expression::Exists(v) => write!(f, "exists {}", v),
+            Expression::Name(v) => write!(f, "{}", v),
+            Expression::Call { name, args } => write!(
+                f,
+                "{} ({})",
+                name,
+                args.iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Expression::Not(v) => match v.as_ref() {
+                Expression::Binary { .. } => write!(f, "not ({})", v),
+                v => write!(f, "not {}", v),
+            },
+            Expression::Binary { op, left, right } => {
+                left.write_operand(f, *op)?;
+                write!(f, " {} ", op)?;
+                right.write_operand(f, *op)
+            }
+        }
+    }
+}
+
+/// Known functions and the number of arguments they take; `None` for a
+/// variable number.
+const FUNCTIONS: &[(&str, Option<usize>)] = &[
+    ("substring", Some(3)),
+    ("suffix", Some(2)),
+    ("concat", None),
+    ("lcase", Some(1)),
+    ("ucase", Some(1)),
+    ("binary-to-ascii", Some(4)),
+    ("encode-int", Some(2)),
+    ("extract-int", Some(2)),
+    ("pick-first-value", None),
+    ("reverse", Some(2)),
+    ("packet", Some(2)),
+    ("gethostbyname", Some(1)),
+    ("v6relay", Some(2)),
+];
+
+/// Maximum nesting of parentheses, negations and function calls, so that
+/// untrusted input cannot overflow the stack.
+const MAX_EXPRESSION_DEPTH: usize = 64;
+
+fn parse_operator(it: &mut Cursor) -> Option<BinaryOp> {
+    match it.peek() {
+        Some(LexItem::Word(w)) => BinaryOp::from(w).ok(),
+        _ => None,
+    }
+}
+
+/// Parse the name following `option`, `config-option` or `exists`.
+fn parse_option_name(it: &mut Cursor, after: &str) -> Result<String, String> {
+    match it.next() {
+        Some(LexItem::Word(w)) => Ok(w.clone()),
+        other => Err(format!(
+            "Expected option name after {}, got {:?}",
+            after, other
+        )),
+    }
+}
+
+fn parse_call(it: &mut Cursor, name: String, depth: usize) -> Result<Expression, String> {
+    it.next(); // "("
+    let mut args = Vec::new();
+    if it.peek() == Some(&&LexItem::Paren(')')) {
+        it.next();
+    } else {
+        loop {
+            args.push(parse_binary(it, 0, depth)?);
+            match it.next() {
+                Some(LexItem::Comma) => {}
+                Some(LexItem::Paren(')')) => break,
+                other => {
+                    return Err(format!(
+                        "Expected ',' or ')' in arguments of {}, got {:?}",
+                        name, other
+                    ))
+                }
+            }
+        }
+    }
+
+    if let Some((_, arity)) = FUNCTIONS.iter().find(|(n, _)| *n == name) {
+        match arity {
+            Some(n) if *n != args.len() => {
+                return Err(format!(
+                    "{} expects {} arguments, got {}",
+                    name,
+                    n,
+                    args.len()
+                ))
+            }
+            None if args.is_empty() => {
+                return Err(format!("{} expects arguments", name));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Expression::Call { name, args })
+}
+
+/// Parse an operand: a constant, name, function call, negation or
+/// parenthesized expression.
+fn parse_primary(it: &mut Cursor, depth: usize) -> Result<Expression, String> {
+    if depth >= MAX_EXPRESSION_DEPTH {
+        return Err(format!(
+            "Expression nested deeper than {} levels",
+            MAX_EXPRESSION_DEPTH
+        ));
+    }
+    let word = match it.next() {
+        Some(LexItem::Paren('(')) => {
+            let e = parse_binary(it, 0, depth + 1)?;
+            return match it.next() {
+                Some(LexItem::Paren(')')) => Ok(e),
+                other => Err(format!("Expected ')', got {:?}", other)),
+            };
+        }
+        Some(LexItem::Str(s)) => return Ok(Expression::Str(s.clone())),
+        Some(LexItem::Word(w)) => w.clone(),
+        Some(t @ LexItem::Opt(_)) | Some(t @ LexItem::Decl(_)) => t.to_string(),
+        other => return Err(format!("Expected expression, got {:?}", other)),
+    };
+
+    let e = match word.as_str() {
+        "not" => Expression::Not(Box::new(parse_primary(it, depth + 1)?)),
+        "option" => Expression::Option(parse_option_name(it, "option")?),
+        "config-option" => Expression::ConfigOption(parse_option_name(it, "config-option")?),
+        "exists" => Expression::Exists(parse_option_name(it, "exists")?),
+        w if w.chars().all(|c| c.is_ascii_digit()) => Expression::Number(
+            w.parse::<u32>()
+                .map_err(|_| format!("Invalid number {:?}", w))?,
+        ),
+        w if w.contains(':') && w.chars().all(|c| c == ':' || c.is_ascii_hexdigit()) => {
+            Expression::Data(decode_hex(w)?)
+        }
+        _ if it.peek() == Some(&&LexItem::Paren('(')) => parse_call(it, word, depth + 1)?,
+        _ => Expression::Name(word),
+    };
+
+    Ok(e)
+}
+
+fn parse_binary(it: &mut Cursor, min_precedence: u8, depth: usize) -> Result<Expression, String> {
+    let mut left = parse_primary(it, depth)?;
+    while let Some(op) = parse_operator(it) {
+        if op.precedence() < min_precedence {
+            break;
+        }
+        it.next();
+        let right = parse_binary(it, op.precedence() + 1, depth)?;
+        left = Expression::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        };
+    }
+
+    Ok(left)
+}
+
+/// Parse an expression, stopping in front of the first token that cannot
+/// continue it, e.g. `;` or `{`.
+pub(crate) fn parse_expression(it: &mut Cursor) -> Result<Expression, String> {
+    parse_binary(it, 0, 0)
+}
@@ -0,0 +1,410 @@
+use crate::common::decode_hex;
+use crate::common::Date;
+use crate::config::Unknown;
+use crate::leases::parse_date;
+use crate::leases::LeaseKeyword;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+use crate::parser::parse_unknown;
+use crate::parser::Context;
+
+/// State of a failover peer as defined by the DHCP failover protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverState {
+    UnknownState,
+    Startup,
+    Normal,
+    CommunicationsInterrupted,
+    PartnerDown,
+    PotentialConflict,
+    ConflictDone,
+    ResolutionInterrupted,
+    Recover,
+    RecoverWait,
+    RecoverDone,
+    Shutdown,
+    Paused,
+}
+
+impl std::fmt::Display for FailoverState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let r = match self {
+            FailoverState::UnknownState => "unknown-state",
+            FailoverState::Startup => "startup",
+            FailoverState::Normal => "normal",
+            FailoverState::CommunicationsInterrupted => "communications-interrupted",
+            FailoverState::PartnerDown => "partner-down",
+            FailoverState::PotentialConflict => "potential-conflict",
+            FailoverState::ConflictDone => "conflict-done",
+            FailoverState::ResolutionInterrupted => "resolution-interrupted",
+            FailoverState::Recover => "recover",
+            FailoverState::RecoverWait => "recover-wait",
+            FailoverState::RecoverDone => "recover-done",
+            FailoverState::Shutdown => "shutdown",
+            FailoverState::Paused => "paused",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl FailoverState {
+    pub fn from(s: &str) -> Result<FailoverState, String> {
+        match s {
+            "unknown-state" => Ok(FailoverState::UnknownState),
+            "startup" => Ok(FailoverState::Startup),
+            "normal" => Ok(FailoverState::Normal),
+            "communications-interrupted" => Ok(FailoverState::CommunicationsInterrupted),
+            "partner-down" => Ok(FailoverState::PartnerDown),
+            "potential-conflict" => Ok(FailoverState::PotentialConflict),
+            "conflict-done" => Ok(FailoverState::ConflictDone),
+            "resolution-interrupted" => Ok(FailoverState::ResolutionInterrupted),
+            "recover" => Ok(FailoverState::Recover),
+            "recover-wait" => Ok(FailoverState::RecoverWait),
+            "recover-done" => Ok(FailoverState::RecoverDone),
+            "shutdown" => Ok(FailoverState::Shutdown),
+            "paused" => Ok(FailoverState::Paused),
+            _ => Err(format!("'{}' is not a failover state", s)),
+        }
+    }
+}
+
+/// A failover state together with the time the peer entered it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverStateEntry {
+    pub state: FailoverState,
+    pub at: Option<Date>,
+}
+
+/// Persisted state of a failover relationship, e.g.
+/// `failover peer "name" state { my state normal at ...; partner state ...; }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverPeerState {
+    pub name: String,
+    pub my_state: Option<FailoverStateEntry>,
+    pub partner_state: Option<FailoverStateEntry>,
+    /// Maximum client lead time in seconds.
+    pub mclt: Option<u32>,
+    /// Entries the library doesn't model.
+    pub unknown: Vec<Unknown>,
+}
+
+/// Parse `state <state> [at <date>]` up to, but excluding, the terminating `;`.
+fn parse_state_entry<'l>(iter: &mut Cursor<'l>, whose: &str) -> Result<FailoverStateEntry, String> {
+    if iter.next() != Some(&LexItem::Opt(LeaseKeyword::State)) {
+        return Err(format!("Expected 'state' after '{}'", whose));
+    }
+    let state = match iter.next() {
+        Some(LexItem::Word(w)) => FailoverState::from(w)?,
+        other => return Err(format!("Expected failover state, found {:?}", other)),
+    };
+
+    let at = match iter.peek().copied() {
+        Some(LexItem::Word(w)) if w.as_str() == "at" => {
+            iter.next();
+            Some(parse_date(iter, "at")?)
+        }
+        Some(LexItem::Endl) => None,
+        other => return Err(format!("Expected 'at' or semicolon, found {:?}", other)),
+    };
+
+    Ok(FailoverStateEntry { state, at })
+}
+
+/// Parse a `failover peer "name" state { ... }` declaration starting at
+/// `failover`, including the closing `}`.
+pub(crate) fn parse_failover_peer_state<'l>(
+    iter: &mut Cursor<'l>,
+    ctx: &Context,
+) -> Result<FailoverPeerState, String> {
+    iter.next(); // "failover"
+    match iter.next() {
+        Some(LexItem::Word(w)) if w.as_str() == "peer" => {}
+        other => return Err(format!("Expected 'peer' after 'failover', got {:?}", other)),
+    }
+    let name = match iter.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => w.clone(),
+        other => return Err(format!("Expected failover peer name, got {:?}", other)),
+    };
+    if iter.next() != Some(&LexItem::Opt(LeaseKeyword::State)) {
+        return Err("Expected 'state' after failover peer name".to_owned());
+    }
+    match iter.next() {
+        Some(LexItem::Paren('{')) => {}
+        other => return Err(format!("Expected '{{' after 'state', got {:?}", other)),
+    }
+
+    let mut peer = FailoverPeerState {
+        name,
+        my_state: None,
+        partner_state: None,
+        mclt: None,
+        unknown: Vec::new(),
+    };
+
+    loop {
+        match iter.peek().copied() {
+            None => return Err("Unexpected EOF inside failover peer block".to_owned()),
+            Some(LexItem::Paren('}')) => {
+                iter.next();
+                return Ok(peer);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "my" => {
+                iter.next();
+                peer.my_state = Some(parse_state_entry(iter, "my")?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "partner" => {
+                iter.next();
+                peer.partner_state = Some(parse_state_entry(iter, "partner")?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "mclt" => {
+                iter.next();
+                peer.mclt = match iter.next() {
+                    Some(LexItem::Word(v)) => Some(
+                        v.parse::<u32>()
+                            .map_err(|_| format!("Invalid mclt value {:?}", v))?,
+                    ),
+                    other => return Err(format!("Expected mclt value, found {:?}", other)),
+                };
+            }
+            Some(_) => {
+                let unknown = parse_unknown(iter, ctx.depth + 1, ctx.settings.max_depth)?;
+                peer.unknown.push(unknown);
+                continue;
+            }
+        }
+        if iter.next() != Some(&LexItem::Endl) {
+            return Err("Semicolon expected inside failover peer block".to_owned());
+        }
+    }
+}
+
+/// Role of a server in a failover pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailoverRole {
+    Primary,
+    Secondary,
+}
+
+impl std::fmt::Display for FailoverRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let r = match self {
+            FailoverRole::Primary => "primary",
+            FailoverRole::Secondary => "secondary",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+/// A `failover peer "name" { ... }` declaration of dhcpd.conf.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FailoverPeer {
+    pub name: String,
+    pub role: Option<FailoverRole>,
+    /// Address this server listens on, an IP address or host name.
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    pub peer_address: Option<String>,
+    pub peer_port: Option<u16>,
+    pub max_response_delay: Option<u32>,
+    pub max_unacked_updates: Option<u32>,
+    /// Maximum client lead time in seconds.
+    pub mclt: Option<u32>,
+    /// Share of the hash buckets served by the primary, out of 256.
+    pub split: Option<u16>,
+    /// Hash bucket assignment bitmap, the alternative to `split`.
+    pub hba: Option<Vec<u8>>,
+    pub load_balance_max_seconds: Option<u32>,
+    pub max_lease_misbalance: Option<u32>,
+    pub max_lease_ownership: Option<u32>,
+    pub min_balance: Option<u32>,
+    pub max_balance: Option<u32>,
+    pub auto_partner_down: Option<u32>,
+}
+
+impl FailoverPeer {
+    /// Differences between this declaration and the one of the partner
+    /// server, which must mirror each other. Empty if they match.
+    pub fn mismatches(&self, partner: &FailoverPeer) -> Vec<String> {
+        let mut res = Vec::new();
+        if self.name != partner.name {
+            res.push(format!("name {:?} != {:?}", self.name, partner.name));
+        }
+        match (self.role, partner.role) {
+            (Some(a), Some(b)) if a != b => {}
+            (a, b) => res.push(format!("roles {:?} and {:?} are not complementary", a, b)),
+        }
+        if self.address != partner.peer_address {
+            res.push(format!(
+                "address {:?} != partner's peer address {:?}",
+                self.address, partner.peer_address
+            ));
+        }
+        if self.peer_address != partner.address {
+            res.push(format!(
+                "peer address {:?} != partner's address {:?}",
+                self.peer_address, partner.address
+            ));
+        }
+        if self.port.is_some() && partner.peer_port.is_some() && self.port != partner.peer_port {
+            res.push(format!(
+                "port {:?} != partner's peer port {:?}",
+                self.port, partner.peer_port
+            ));
+        }
+        if self.peer_port.is_some() && partner.port.is_some() && self.peer_port != partner.port {
+            res.push(format!(
+                "peer port {:?} != partner's port {:?}",
+                self.peer_port, partner.port
+            ));
+        }
+        // Only the primary needs mclt and split; the secondary may repeat them.
+        if self.mclt.is_some() && partner.mclt.is_some() && self.mclt != partner.mclt {
+            res.push(format!("mclt {:?} != {:?}", self.mclt, partner.mclt));
+        }
+        if self.split.is_some() && partner.split.is_some() && self.split != partner.split {
+            res.push(format!("split {:?} != {:?}", self.split, partner.split));
+        }
+        if self.max_response_delay != partner.max_response_delay {
+            res.push(format!(
+                "max-response-delay {:?} != {:?}",
+                self.max_response_delay, partner.max_response_delay
+            ));
+        }
+        if self.max_unacked_updates != partner.max_unacked_updates {
+            res.push(format!(
+                "max-unacked-updates {:?} != {:?}",
+                self.max_unacked_updates, partner.max_unacked_updates
+            ));
+        }
+        res
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(iter: &mut Cursor, what: &str) -> Result<T, String> {
+    match iter.next() {
+        Some(LexItem::Word(v)) => v
+            .parse::<T>()
+            .map_err(|_| format!("Invalid {} value {:?}", what, v)),
+        other => Err(format!("Expected {} value, found {:?}", what, other)),
+    }
+}
+
+fn parse_address(iter: &mut Cursor) -> Result<String, String> {
+    match iter.next() {
+        Some(LexItem::Word(v)) | Some(LexItem::Str(v)) => Ok(v.clone()),
+        other => Err(format!("Expected address, found {:?}", other)),
+    }
+}
+
+fn expect_word(iter: &mut Cursor, word: &str, after: &str) -> Result<(), String> {
+    match iter.next() {
+        Some(LexItem::Word(w)) if w == word => Ok(()),
+        other => Err(format!(
+            "Expected '{}' after '{}', found {:?}",
+            word, after, other
+        )),
+    }
+}
+
+/// Parse the `failover peer "name"` prefix of a declaration or reference,
+/// returning the name.
+pub(crate) fn parse_failover_peer_name(iter: &mut Cursor) -> Result<String, String> {
+    iter.next(); // "failover"
+    match iter.next() {
+        Some(LexItem::Word(w)) if w.as_str() == "peer" => {}
+        other => return Err(format!("Expected 'peer' after 'failover', got {:?}", other)),
+    }
+    match iter.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => Ok(w.clone()),
+        other => Err(format!("Expected failover peer name, got {:?}", other)),
+    }
+}
+
+/// Parse a `failover peer "name" { ... }` declaration starting at
+/// `failover`, including the closing `}`.
+pub(crate) fn parse_failover_peer(iter: &mut Cursor) -> Result<FailoverPeer, String> {
+    let mut peer = FailoverPeer {
+        name: parse_failover_peer_name(iter)?,
+        ..FailoverPeer::default()
+    };
+    match iter.next() {
+        Some(LexItem::Paren('{')) => {}
+        other => {
+            return Err(format!(
+                "Expected '{{' after failover peer name, got {:?}",
+                other
+            ))
+        }
+    }
+
+    loop {
+        let word = match iter.next() {
+            None => return Err("Unexpected EOF inside failover peer block".to_owned()),
+            Some(LexItem::Paren('}')) => return Ok(peer),
+            Some(LexItem::Word(w)) => w.as_str(),
+            Some(other) => return Err(format!("Unexpected {:?} in failover peer block", other)),
+        };
+        match word {
+            "primary" => peer.role = Some(FailoverRole::Primary),
+            "secondary" => peer.role = Some(FailoverRole::Secondary),
+            "address" => peer.address = Some(parse_address(iter)?),
+            "port" => peer.port = Some(parse_number(iter, "port")?),
+            "peer" => match iter.next() {
+                Some(LexItem::Word(w)) if w.as_str() == "address" => {
+                    peer.peer_address = Some(parse_address(iter)?)
+                }
+                Some(LexItem::Word(w)) if w.as_str() == "port" => {
+                    peer.peer_port = Some(parse_number(iter, "peer port")?)
+                }
+                other => {
+                    return Err(format!(
+                        "Expected 'address' or 'port' after 'peer', found {:?}",
+                        other
+                    ))
+                }
+            },
+            "max-response-delay" => {
+                peer.max_response_delay = Some(parse_number(iter, "max-response-delay")?)
+            }
+            "max-unacked-updates" => {
+                peer.max_unacked_updates = Some(parse_number(iter, "max-unacked-updates")?)
+            }
+            "mclt" => peer.mclt = Some(parse_number(iter, "mclt")?),
+            "split" => {
+                let split: u16 = parse_number(iter, "split")?;
+                if split > 256 {
+                    return Err(format!("Invalid split value \"{}\"", split));
+                }
+                peer.split = Some(split);
+            }
+            "hba" => {
+                peer.hba = Some(match iter.next() {
+                    Some(LexItem::Word(v)) => decode_hex(v)?,
+                    other => return Err(format!("Expected hba bitmap, found {:?}", other)),
+                })
+            }
+            "load" => {
+                expect_word(iter, "balance", "load")?;
+                expect_word(iter, "max", "balance")?;
+                expect_word(iter, "seconds", "max")?;
+                peer.load_balance_max_seconds =
+                    Some(parse_number(iter, "load balance max seconds")?);
+            }
+            "max-lease-misbalance" => {
+                peer.max_lease_misbalance = Some(parse_number(iter, "max-lease-misbalance")?)
+            }
+            "max-lease-ownership" => {
+                peer.max_lease_ownership = Some(parse_number(iter, "max-lease-ownership")?)
+            }
+            "min-balance" => peer.min_balance = Some(parse_number(iter, "min-balance")?),
+            "max-balance" => peer.max_balance = Some(parse_number(iter, "max-balance")?),
+            "auto-partner-down" => {
+                peer.auto_partner_down = Some(parse_number(iter, "auto-partner-down")?)
+            }
+            _ => return Err(format!("Unknown failover peer statement {:?}", word)),
+        }
+        if iter.next() != Some(&LexItem::Endl) {
+            return Err("Semicolon expected inside failover peer block".to_owned());
+        }
+    }
+}
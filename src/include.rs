@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::Statement;
+use crate::lex::lex;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+use crate::parser::check_tokens;
+use crate::parser::parse_declarations;
+use crate::parser::Context;
+
+/// Source of the files named by `include` statements.
+pub trait FileLoader {
+    /// Contents of the file at `path`, as written in the `include` statement.
+    fn load(&self, path: &str) -> Result<String, String>;
+
+    /// Name identifying the file at `path`, used to detect include cycles
+    /// between paths written differently. Defaults to `path` with `.` and
+    /// `..` components resolved.
+    fn canonical(&self, path: &str) -> String {
+        normalize(path)
+    }
+}
+
+/// Resolve `.` and `..` components and repeated separators of `path` without
+/// looking at the filesystem.
+fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|p| *p != "..") => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    let normalized = parts.join("/");
+    if path.starts_with('/') {
+        format!("/{}", normalized)
+    } else {
+        normalized
+    }
+}
+
+/// Loads files from the filesystem. Relative paths are resolved against the
+/// root directory if one is set, and against the working directory like
+/// dhcpd does otherwise.
+#[derive(Debug, Clone, Default)]
+pub struct FsLoader {
+    root: Option<PathBuf>,
+}
+
+impl FsLoader {
+    pub fn new() -> FsLoader {
+        FsLoader::default()
+    }
+
+    pub fn with_root<P: Into<PathBuf>>(root: P) -> FsLoader {
+        FsLoader {
+            root: Some(root.into()),
+        }
+    }
+}
+
+impl FsLoader {
+    fn full_path(&self, path: &str) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+}
+
+impl FileLoader for FsLoader {
+    fn load(&self, path: &str) -> Result<String, String> {
+        let full = self.full_path(path);
+        fs::read_to_string(&full).map_err(|err| format!("Cannot read {:?}: {}", full, err))
+    }
+
+    /// The absolute path with symbolic links resolved. Files that cannot be
+    /// resolved, e.g. because they are missing, fall back to the lexically
+    /// normalized path so that loading them reports the error.
+    fn canonical(&self, path: &str) -> String {
+        let full = self.full_path(path);
+        match fs::canonicalize(&full) {
+            Ok(canonical) => canonical.to_string_lossy().into_owned(),
+            Err(_) => normalize(&full.to_string_lossy()),
+        }
+    }
+}
+
+/// Serves files from memory, e.g. for tests.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    files: HashMap<String, String>,
+}
+
+impl MemoryLoader {
+    pub fn new() -> MemoryLoader {
+        MemoryLoader::default()
+    }
+
+    pub fn insert<P: Into<String>, S: Into<String>>(&mut self, path: P, contents: S) {
+        self.files.insert(path.into(), contents.into());
+    }
+}
+
+impl FileLoader for MemoryLoader {
+    fn load(&self, path: &str) -> Result<String, String> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| format!("No such file {:?}", path))
+    }
+}
+
+/// `include "file";`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Include {
+    pub path: String,
+    /// Statements of the included file, `None` when parsing without a
+    /// [`FileLoader`].
+    pub statements: Option<Vec<Statement>>,
+}
+
+/// Load and parse the statements of `path`. Spans of the statements record
+/// the file, as do errors, which are returned fully located.
+pub(crate) fn parse_included(path: &str, ctx: &mut Context) -> Result<Vec<Statement>, String> {
+    let loader = match ctx.loader {
+        Some(loader) => loader,
+        None => return Err(format!("Cannot include {:?} without a file loader", path)),
+    };
+    let canonical = loader.canonical(path);
+    if ctx.include_stack.contains(&canonical) {
+        let mut chain = ctx.include_stack.clone();
+        chain.push(canonical);
+        return Err(format!("Include cycle: {}", chain.join(" -> ")));
+    }
+
+    let input = loader.load(path)?;
+    let file: Arc<str> = Arc::from(path);
+    let mut tokens = lex(input).map_err(|err| {
+        ctx.error_located = true;
+        format!("{}: Lexer error: {}", file, err)
+    })?;
+    for token in &mut tokens {
+        token.span.file = Some(file.clone());
+    }
+    check_tokens(&tokens, ctx)?;
+    ctx.include_stack.push(canonical);
+    let mut it = Cursor::new(&tokens);
+    let statements = parse_declarations(&mut it, ctx, false).map_err(|err| {
+        if ctx.error_located {
+            return err;
+        }
+        ctx.error_located = true;
+        let span = it.prev_span().cloned().unwrap_or_else(|| it.span());
+        format!("{}: {}", span, err)
+    })?;
+    ctx.include_stack.pop();
+
+    Ok(statements)
+}
+
+/// Parse an `include` statement starting at its keyword, including the `;`,
+/// and the file it names if a loader is set.
+pub(crate) fn parse_include(it: &mut Cursor, ctx: &mut Context) -> Result<Include, String> {
+    it.next(); // "include"
+    let path = match it.next() {
+        Some(LexItem::Str(v)) => v.clone(),
+        other => return Err(format!("Expected file name after include, got {:?}", other)),
+    };
+    if it.next() != Some(&LexItem::Endl) {
+        return Err(format!("Expected ';' after include {:?}", path));
+    }
+
+    let statements = match ctx.loader {
+        Some(_) => Some(parse_included(&path, ctx)?),
+        None => None,
+    };
+
+    Ok(Include { path, statements })
+}
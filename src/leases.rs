@@ -1,20 +1,36 @@
 use std::collections::HashSet;
-use std::iter::Peekable;
 use std::ops::Index;
 
 use crate::common::Date;
+use crate::config::Unknown;
+use crate::dialect::Dialect;
+use crate::lex::Cursor;
 use crate::lex::LexItem;
+use crate::parser::parse_unknown;
+use crate::parser::Context;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum BindingState {
     Active,
+    #[default]
     Free,
     Abandoned,
+    Expired,
+    Released,
+    Backup,
 }
 
-impl Default for BindingState {
-    fn default() -> Self {
-        Self::Free
+impl std::fmt::Display for BindingState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let r = match self {
+            BindingState::Active => "active",
+            BindingState::Free => "free",
+            BindingState::Abandoned => "abandoned",
+            BindingState::Expired => "expired",
+            BindingState::Released => "released",
+            BindingState::Backup => "backup",
+        };
+        write!(f, "{}", r)
     }
 }
 
@@ -107,8 +123,10 @@ pub enum LeasesField {
     MAC,
 }
 
+type FieldGetter = Box<dyn Fn(&Lease) -> Option<String>>;
+
 impl LeasesField {
-    fn value_getter(&self) -> Box<dyn Fn(&Lease) -> Option<String>> {
+    fn value_getter(&self) -> FieldGetter {
         match &self {
             LeasesField::ClientHostname => {
                 Box::new(|l: &Lease| -> Option<String> { l.client_hostname.clone() })
@@ -168,6 +186,7 @@ pub trait LeasesMethods {
     #[deprecated(since = "0.4.3", note = "any filtering logic should be done by user")]
     fn by_client_hostname_all<S: AsRef<str>>(&self, hostname: S) -> Vec<Lease>;
 
+    #[allow(clippy::new_ret_no_self)]
     fn new() -> Leases;
     fn push(&mut self, l: Lease);
     fn hostnames(&self) -> HashSet<String>;
@@ -315,8 +334,8 @@ impl LeasesMethods for Leases {
         let ls = self.0.clone();
 
         for l in ls {
-            if l.hostname.is_some() {
-                res.insert(l.hostname.unwrap());
+            if let Some(hostname) = l.hostname {
+                res.insert(hostname);
             }
         }
 
@@ -328,8 +347,8 @@ impl LeasesMethods for Leases {
         let ls = self.0.clone();
 
         for l in ls {
-            if l.client_hostname.is_some() {
-                res.insert(l.client_hostname.unwrap());
+            if let Some(hostname) = l.client_hostname {
+                res.insert(hostname);
             }
         }
 
@@ -349,6 +368,12 @@ pub struct Lease {
     pub next_binding_state: Option<BindingState>,
     pub rewind_binding_state: Option<BindingState>,
     pub vendor_class_identifier: Option<String>,
+    /// Other `set` variables, e.g. `ddns-fwd-name`, in order, with their
+    /// values as written.
+    pub variables: Vec<(String, String)>,
+    /// Entries the library doesn't model, kept when leases aren't parsed
+    /// strictly.
+    pub unknown: Vec<Unknown>,
 }
 
 impl Lease {
@@ -365,10 +390,7 @@ impl Lease {
     }
 }
 
-pub fn parse_date<'l, T: Iterator<Item = &'l LexItem>>(
-    iter: &mut Peekable<T>,
-    name: &str,
-) -> Result<crate::common::Date, String> {
+pub fn parse_date<'l>(iter: &mut Cursor<'l>, name: &str) -> Result<crate::common::Date, String> {
     let weekday = match iter.peek() {
         Some(v) => v.to_string(),
         None => return Err(format!("Weekday for {:?} date expected", name)),
@@ -410,9 +432,7 @@ pub fn parse_date<'l, T: Iterator<Item = &'l LexItem>>(
     Date::from(weekday, date, time)
 }
 
-pub fn parse_binding_state<'l, T: Iterator<Item = &'l LexItem>>(
-    iter: &mut Peekable<T>,
-) -> Result<BindingState, String> {
+pub fn parse_binding_state<'l>(iter: &mut Cursor<'l>) -> Result<BindingState, String> {
     iter.next();
     if iter.peek() != Some(&&LexItem::Opt(LeaseKeyword::State)) {
         return Err("Expected 'state' after 'binding'".to_owned());
@@ -424,6 +444,9 @@ pub fn parse_binding_state<'l, T: Iterator<Item = &'l LexItem>>(
             "active" => BindingState::Active,
             "free" => BindingState::Free,
             "abandoned" => BindingState::Abandoned,
+            "expired" => BindingState::Expired,
+            "released" => BindingState::Released,
+            "backup" => BindingState::Backup,
             _ => return Err(format!("Expected binding value, found {:?}", iter.peek())),
         }
     } else {
@@ -438,11 +461,37 @@ pub fn parse_binding_state<'l, T: Iterator<Item = &'l LexItem>>(
     Ok(r)
 }
 
-pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
+pub fn parse_lease<'l>(lease: &mut Lease, iter: &mut Cursor<'l>) -> Result<(), String> {
+    parse_lease_entries(lease, iter, &mut Context::default())
+}
+
+/// The dialect that accepts a lease entry starting with `item`, and how to
+/// describe the entry, when only one of them does.
+fn lease_entry_dialect(item: &LexItem) -> Option<(Dialect, &'static str)> {
+    match item {
+        LexItem::Opt(LeaseKeyword::Tstp) => Some((Dialect::Isc, "tstp")),
+        LexItem::Opt(LeaseKeyword::Tsfp) => Some((Dialect::Isc, "tsfp")),
+        LexItem::Opt(LeaseKeyword::Atsfp) => Some((Dialect::Isc, "atsfp")),
+        LexItem::Opt(LeaseKeyword::Cltt) => Some((Dialect::Isc, "cltt")),
+        LexItem::Opt(LeaseKeyword::Binding) => Some((Dialect::Isc, "binding state")),
+        LexItem::Opt(LeaseKeyword::Next) => Some((Dialect::Isc, "next binding state")),
+        LexItem::Opt(LeaseKeyword::Rewind) => Some((Dialect::Isc, "rewind binding state")),
+        LexItem::Opt(LeaseKeyword::Set) => Some((Dialect::Isc, "set")),
+        LexItem::Opt(LeaseKeyword::Hostname) => Some((Dialect::OpenBsd, "hostname")),
+        LexItem::Word(w) if w.as_str() == "abandoned" => Some((Dialect::OpenBsd, "abandoned")),
+        _ => None,
+    }
+}
+
+/// Parse the entries of a lease block, checking them against the dialect
+/// of `ctx`. Unknown entries are kept unless leases are parsed strictly.
+pub(crate) fn parse_lease_entries<'l>(
     lease: &mut Lease,
-    iter: &mut Peekable<T>,
+    iter: &mut Cursor<'l>,
+    ctx: &mut Context,
 ) -> Result<(), String> {
     while let Some(&nc) = iter.peek() {
+        let entry_dialect = lease_entry_dialect(nc);
         match nc {
             LexItem::Opt(LeaseKeyword::Starts) => {
                 iter.next();
@@ -556,10 +605,9 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
                 }
 
                 iter.next();
-                let value = if let Some(LexItem::Word(w)) = iter.peek() {
-                    w
-                } else {
-                    return Err("Value name expected after '='".to_string());
+                let value = match iter.peek().copied() {
+                    Some(v @ LexItem::Word(_)) | Some(v @ LexItem::Str(_)) => v,
+                    _ => return Err("Value name expected after '='".to_string()),
                 };
 
                 iter.next();
@@ -569,15 +617,32 @@ pub fn parse_lease<'l, T: Iterator<Item = &'l LexItem>>(
 
                 if let "vendor-class-identifier" = name.as_str() {
                     let _ = lease.vendor_class_identifier.replace(value.to_string());
+                } else {
+                    lease.variables.push((name.to_string(), value.raw()));
+                }
+            }
+            LexItem::Word(w) if w.as_str() == "abandoned" => {
+                iter.next();
+                if iter.peek() != Some(&&LexItem::Endl) {
+                    return Err("Semicolon expected after abandoned".to_owned());
                 }
+                lease.binding_state = BindingState::Abandoned;
             }
             LexItem::Paren('}') => {
                 return Ok(());
             }
+            _ if !ctx.settings.strict_leases => {
+                let unknown = parse_unknown(iter, ctx.depth + 1, ctx.settings.max_depth)?;
+                lease.unknown.push(unknown);
+                continue;
+            }
             _ => {
                 return Err(format!("Unexpected option '{}'", iter.peek().unwrap()));
             }
         }
+        if let Some((d, what)) = entry_dialect {
+            ctx.dialect.construct(d, what)?;
+        }
         iter.next();
     }
 
@@ -0,0 +1,298 @@
+use std::net::Ipv6Addr;
+
+use crate::common::decode_escaped;
+use crate::common::Date;
+use crate::config::Unknown;
+use crate::duid::Duid;
+use crate::leases::parse_binding_state;
+use crate::leases::parse_date;
+use crate::leases::BindingState;
+use crate::leases::LeaseKeyword;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+use crate::parser::parse_unknown;
+use crate::parser::Context;
+
+/// Kind of an identity association, i.e. the keyword that opened it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IaKind {
+    /// `ia-na`: non-temporary addresses
+    Na,
+    /// `ia-ta`: temporary addresses
+    Ta,
+    /// `ia-pd`: delegated prefixes
+    Pd,
+}
+
+impl std::fmt::Display for IaKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IaKind::Na => write!(f, "ia-na"),
+            IaKind::Ta => write!(f, "ia-ta"),
+            IaKind::Pd => write!(f, "ia-pd"),
+        }
+    }
+}
+
+impl IaKind {
+    pub fn from(s: &str) -> Result<IaKind, String> {
+        match s {
+            "ia-na" => Ok(IaKind::Na),
+            "ia-ta" => Ok(IaKind::Ta),
+            "ia-pd" => Ok(IaKind::Pd),
+            _ => Err(format!("'{}' is not an identity association", s)),
+        }
+    }
+}
+
+/// Byte order declared by the `authoring-byte-order` entry of the leases file.
+///
+/// ISC dhcpd writes the IAID part of `ia-*` identifiers in the native byte
+/// order of the host that wrote the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    LittleEndian,
+    BigEndian,
+}
+
+impl Default for ByteOrder {
+    /// Files written before `authoring-byte-order` was introduced come almost
+    /// exclusively from little-endian hosts.
+    fn default() -> Self {
+        ByteOrder::LittleEndian
+    }
+}
+
+impl std::fmt::Display for ByteOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ByteOrder::LittleEndian => write!(f, "little-endian"),
+            ByteOrder::BigEndian => write!(f, "big-endian"),
+        }
+    }
+}
+
+impl ByteOrder {
+    pub fn from(s: &str) -> Result<ByteOrder, String> {
+        match s {
+            "little-endian" => Ok(ByteOrder::LittleEndian),
+            "big-endian" => Ok(ByteOrder::BigEndian),
+            _ => Err(format!("'{}' is not a valid byte order", s)),
+        }
+    }
+}
+
+/// An address bound to an `ia-na` or `ia-ta`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IaAddr {
+    pub address: Ipv6Addr,
+    pub binding_state: BindingState,
+    pub preferred_life: Option<u32>,
+    pub max_life: Option<u32>,
+    pub ends: Option<Date>,
+    /// Entries the library doesn't model, e.g. `set` variables and `on`
+    /// blocks.
+    pub unknown: Vec<Unknown>,
+}
+
+/// A prefix delegated through an `ia-pd`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IaPrefix {
+    pub prefix: Ipv6Addr,
+    pub prefix_len: u8,
+    pub binding_state: BindingState,
+    pub preferred_life: Option<u32>,
+    pub max_life: Option<u32>,
+    pub ends: Option<Date>,
+    /// Entries the library doesn't model, e.g. `set` variables and `on`
+    /// blocks.
+    pub unknown: Vec<Unknown>,
+}
+
+/// An identity association from `dhcpd6.leases`, e.g.
+/// `ia-na "\001\000\000\000\000\001..." { cltt ...; iaaddr 2001:db8::10 { ... } }`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ia {
+    pub kind: IaKind,
+    pub iaid: u32,
+    /// DUID of the client, i.e. the identifier without the leading IAID.
+    pub duid: Duid,
+    pub cltt: Option<Date>,
+    pub addresses: Vec<IaAddr>,
+    pub prefixes: Vec<IaPrefix>,
+    /// Entries the library doesn't model, e.g. `set` variables and `on`
+    /// blocks.
+    pub unknown: Vec<Unknown>,
+}
+
+/// Lifetimes and state shared by `iaaddr` and `iaprefix` blocks.
+#[derive(Default)]
+struct Binding {
+    binding_state: BindingState,
+    preferred_life: Option<u32>,
+    max_life: Option<u32>,
+    ends: Option<Date>,
+    unknown: Vec<Unknown>,
+}
+
+fn parse_lifetime<'l>(iter: &mut Cursor<'l>, name: &str) -> Result<u32, String> {
+    iter.next();
+    let v = match iter.next() {
+        Some(LexItem::Word(w)) => w
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid {} value {:?}", name, w))?,
+        other => return Err(format!("Expected {} value, found {:?}", name, other)),
+    };
+    if iter.peek() != Some(&&LexItem::Endl) {
+        return Err(format!("Semicolon expected after {}", name));
+    }
+
+    Ok(v)
+}
+
+/// Parse the body of an `iaaddr`/`iaprefix` block, including the closing `}`.
+fn parse_binding<'l>(iter: &mut Cursor<'l>, ctx: &Context) -> Result<Binding, String> {
+    let mut binding = Binding::default();
+
+    loop {
+        match iter.peek().copied() {
+            None => return Err("Unexpected EOF inside address block".to_owned()),
+            Some(LexItem::Paren('}')) => {
+                iter.next();
+                return Ok(binding);
+            }
+            Some(LexItem::Opt(LeaseKeyword::Binding)) => {
+                binding.binding_state = parse_binding_state(iter)?;
+            }
+            Some(LexItem::Opt(LeaseKeyword::Ends)) => {
+                iter.next();
+                binding.ends.replace(parse_date(iter, "end")?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "preferred-life" => {
+                binding.preferred_life = Some(parse_lifetime(iter, "preferred-life")?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "max-life" => {
+                binding.max_life = Some(parse_lifetime(iter, "max-life")?);
+            }
+            Some(_) => {
+                let unknown = parse_unknown(iter, ctx.depth + 2, ctx.settings.max_depth)?;
+                binding.unknown.push(unknown);
+                continue;
+            }
+        }
+        iter.next();
+    }
+}
+
+fn expect_open_brace<'l>(iter: &mut Cursor<'l>, after: &str) -> Result<(), String> {
+    match iter.next() {
+        Some(LexItem::Paren('{')) => Ok(()),
+        other => Err(format!("Expected '{{' after {}, got {:?}", after, other)),
+    }
+}
+
+/// Parse an `ia-na`, `ia-ta` or `ia-pd` declaration starting at its keyword,
+/// including the closing `}`.
+pub(crate) fn parse_ia<'l>(iter: &mut Cursor<'l>, ctx: &Context) -> Result<Ia, String> {
+    let kind = match iter.next() {
+        Some(LexItem::Word(w)) => IaKind::from(w)?,
+        other => return Err(format!("Expected identity association, got {:?}", other)),
+    };
+    let id = match iter.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => decode_escaped(w)?,
+        other => return Err(format!("Expected {} identifier, got {:?}", kind, other)),
+    };
+    if id.len() < 4 {
+        return Err(format!("{} identifier is too short to hold an IAID", kind));
+    }
+    let (iaid, duid) = id.split_at(4);
+    let iaid = [iaid[0], iaid[1], iaid[2], iaid[3]];
+    let iaid = match ctx.byte_order {
+        ByteOrder::LittleEndian => u32::from_le_bytes(iaid),
+        ByteOrder::BigEndian => u32::from_be_bytes(iaid),
+    };
+    expect_open_brace(iter, &kind.to_string())?;
+
+    let mut ia = Ia {
+        kind,
+        iaid,
+        duid: Duid::from_bytes(duid),
+        cltt: None,
+        addresses: Vec::new(),
+        prefixes: Vec::new(),
+        unknown: Vec::new(),
+    };
+
+    loop {
+        match iter.peek().copied() {
+            None => return Err(format!("Unexpected EOF inside {} block", kind)),
+            Some(LexItem::Paren('}')) => {
+                iter.next();
+                return Ok(ia);
+            }
+            Some(LexItem::Opt(LeaseKeyword::Cltt)) => {
+                iter.next();
+                ia.cltt.replace(parse_date(iter, "cltt")?);
+                iter.next();
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "iaaddr" => {
+                iter.next();
+                let address = match iter.next() {
+                    Some(LexItem::Word(a)) => a
+                        .parse::<Ipv6Addr>()
+                        .map_err(|_| format!("Invalid iaaddr address {:?}", a))?,
+                    other => return Err(format!("Expected iaaddr address, got {:?}", other)),
+                };
+                expect_open_brace(iter, "iaaddr")?;
+                let b = parse_binding(iter, ctx)?;
+                ia.addresses.push(IaAddr {
+                    address,
+                    binding_state: b.binding_state,
+                    preferred_life: b.preferred_life,
+                    max_life: b.max_life,
+                    ends: b.ends,
+                    unknown: b.unknown,
+                });
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "iaprefix" => {
+                iter.next();
+                let (prefix, prefix_len) = match iter.next() {
+                    Some(LexItem::Word(p)) => parse_prefix(p)?,
+                    other => return Err(format!("Expected iaprefix prefix, got {:?}", other)),
+                };
+                expect_open_brace(iter, "iaprefix")?;
+                let b = parse_binding(iter, ctx)?;
+                ia.prefixes.push(IaPrefix {
+                    prefix,
+                    prefix_len,
+                    binding_state: b.binding_state,
+                    preferred_life: b.preferred_life,
+                    max_life: b.max_life,
+                    ends: b.ends,
+                    unknown: b.unknown,
+                });
+            }
+            Some(_) => {
+                let unknown = parse_unknown(iter, ctx.depth + 1, ctx.settings.max_depth)?;
+                ia.unknown.push(unknown);
+            }
+        }
+    }
+}
+
+/// Parse an IPv6 prefix in `address/length` notation.
+pub fn parse_prefix(s: &str) -> Result<(Ipv6Addr, u8), String> {
+    let (addr, len) = match s.split_once('/') {
+        Some(v) => v,
+        None => return Err(format!("{:?} is not an IPv6 prefix", s)),
+    };
+    let addr = addr
+        .parse::<Ipv6Addr>()
+        .map_err(|_| format!("{:?} is not an IPv6 prefix", s))?;
+    let len = match len.parse::<u8>() {
+        Ok(l) if l <= 128 => l,
+        _ => return Err(format!("Invalid prefix length in {:?}", s)),
+    };
+
+    Ok((addr, len))
+}
@@ -1,6 +1,9 @@
 use std::fmt;
 use std::iter::Peekable;
+use std::str::Chars;
 
+use crate::common::quote;
+use crate::common::Span;
 use crate::leases::LeaseKeyword;
 use crate::parser::ConfigKeyword;
 
@@ -8,7 +11,10 @@ use crate::parser::ConfigKeyword;
 pub enum LexItem {
     Paren(char),
     Endl,
+    Comma,
     Word(String),
+    /// Contents of a double-quoted string
+    Str(String),
     Opt(LeaseKeyword),
     Decl(ConfigKeyword),
 }
@@ -18,14 +24,89 @@ impl fmt::Display for LexItem {
         match self {
             LexItem::Paren(v) => v.fmt(f),
             LexItem::Word(v) => v.fmt(f),
+            LexItem::Str(v) => v.fmt(f),
             LexItem::Opt(v) => write!(f, "{}", v),
             LexItem::Decl(v) => write!(f, "{}", v),
             LexItem::Endl => write!(f, ";"),
+            LexItem::Comma => write!(f, ","),
         }
     }
 }
 
-fn parse_double_quoted<T: Iterator<Item = char>>(it: &mut Peekable<T>) -> Result<String, String> {
+impl LexItem {
+    /// The token as it would be written in a configuration file, i.e. with
+    /// strings quoted.
+    pub fn raw(&self) -> String {
+        match self {
+            LexItem::Str(v) => quote(v),
+            v => v.to_string(),
+        }
+    }
+}
+
+/// A lexed token together with its location in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub item: LexItem,
+    pub span: Span,
+}
+
+/// Characters of the input, keeping track of the current location.
+struct Input<'a> {
+    chars: Peekable<Chars<'a>>,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Input<'a> {
+    fn new(s: &'a str) -> Input<'a> {
+        Input {
+            chars: s.chars().peekable(),
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    /// Span starting at the current location, to be closed by `end_span`.
+    fn start_span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset,
+            line: self.line,
+            column: self.column,
+            file: None,
+        }
+    }
+
+    fn end_span(&self, mut span: Span) -> Span {
+        span.end = self.offset;
+        span
+    }
+}
+
+impl<'a> Iterator for Input<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+}
+
+fn parse_double_quoted(it: &mut Input) -> Result<String, String> {
     let mut result = String::new();
 
     it.next();
@@ -37,18 +118,18 @@ fn parse_double_quoted<T: Iterator<Item = char>>(it: &mut Peekable<T>) -> Result
                 Some('"') => result.push('"'),
                 Some(c) => {
                     result.push('\\');
-                    result.push(c.clone());
+                    result.push(c);
                 }
             },
             '"' => return Ok(result),
-            c => result.push(c.clone()),
+            c => result.push(c),
         }
     }
 
     Err("Unexpected EOF after backslash".to_string())
 }
 
-pub fn lex<S>(input: S) -> Result<Vec<LexItem>, String>
+pub fn lex<S>(input: S) -> Result<Vec<Token>, String>
 where
     S: Into<String>,
 {
@@ -56,55 +137,76 @@ where
 
     let input_str = input.into();
 
-    let mut it = input_str.chars().peekable();
+    let mut it = Input::new(&input_str);
     while let Some(&c) = it.peek() {
-        match c {
-            '(' | ')' | '[' | ']' | '{' | '}' => {
-                result.push(LexItem::Paren(c));
-                it.next();
-            }
-            '#' => {
-                while let Some(&c) = it.peek() {
-                    if c == '\n' {
-                        break;
+        let span = it.start_span();
+        let item =
+            match c {
+                '(' | ')' | '[' | ']' | '{' | '}' => {
+                    it.next();
+                    LexItem::Paren(c)
+                }
+                '#' => {
+                    while let Some(&c) = it.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        it.next();
                     }
+                    continue;
+                }
+                c if c.is_whitespace() => {
                     it.next();
+                    continue;
                 }
-            }
-            ' ' | '\n' | '\t' => {
-                it.next();
-            }
-            '"' => {
-                result.push(LexItem::Word(parse_double_quoted(&mut it)?));
-            }
-            ';' => {
-                result.push(LexItem::Endl);
-                it.next();
-            }
-            _ => {
-                let w = get_word(&mut it);
-                let kw = ConfigKeyword::from(&w);
-                if let Ok(kw) = kw {
-                    result.push(LexItem::Decl(kw));
-                } else {
-                    let kw = LeaseKeyword::from(&w);
+                '"' => LexItem::Str(parse_double_quoted(&mut it).map_err(|err| {
+                    format!("line {}, column {}: {}", span.line, span.column, err)
+                })?),
+                ';' => {
+                    it.next();
+                    LexItem::Endl
+                }
+                ',' => {
+                    it.next();
+                    LexItem::Comma
+                }
+                '=' => {
+                    it.next();
+                    LexItem::Word("=".to_owned())
+                }
+                _ => {
+                    let mut w = get_word(&mut it);
+                    // `!=` and `~=` operators
+                    if (w == "!" || w == "~") && it.peek() == Some(&'=') {
+                        it.next();
+                        w.push('=');
+                    }
+                    let kw = ConfigKeyword::from(&w);
                     if let Ok(kw) = kw {
-                        result.push(LexItem::Opt(kw));
+                        LexItem::Decl(kw)
                     } else {
-                        result.push(LexItem::Word(w));
+                        let kw = LeaseKeyword::from(&w);
+                        if let Ok(kw) = kw {
+                            LexItem::Opt(kw)
+                        } else {
+                            LexItem::Word(w)
+                        }
                     }
                 }
-            }
-        }
+            };
+        result.push(Token {
+            item,
+            span: it.end_span(span),
+        });
     }
     Ok(result)
 }
 
-fn get_word<T: Iterator<Item = char>>(iter: &mut Peekable<T>) -> String {
+fn get_word(iter: &mut Input) -> String {
     let mut word = String::new();
 
     while let Some(&nc) = iter.peek() {
-        if nc.is_whitespace() || nc == ';' {
+        if nc.is_whitespace() || ";,{}()=".contains(nc) {
             break;
         }
 
@@ -113,3 +215,73 @@ fn get_word<T: Iterator<Item = char>>(iter: &mut Peekable<T>) -> String {
     }
     word
 }
+
+/// Position in a token stream. Mirrors the `Peekable` interface the parsing
+/// functions are written against, and additionally knows where each token
+/// came from.
+#[derive(Clone)]
+pub struct Cursor<'l> {
+    tokens: &'l [Token],
+    pos: usize,
+    peeked: Option<&'l LexItem>,
+}
+
+impl<'l> Cursor<'l> {
+    pub fn new(tokens: &'l [Token]) -> Cursor<'l> {
+        Cursor {
+            tokens,
+            pos: 0,
+            peeked: None,
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<&&'l LexItem> {
+        self.peeked = self.tokens.get(self.pos).map(|t| &t.item);
+        self.peeked.as_ref()
+    }
+
+    /// Span of the next token, or an empty span at the end of the input.
+    pub fn span(&self) -> Span {
+        match self.tokens.get(self.pos) {
+            Some(t) => t.span.clone(),
+            None => match self.tokens.last() {
+                Some(t) => Span {
+                    start: t.span.end,
+                    ..t.span.clone()
+                },
+                None => Span::default(),
+            },
+        }
+    }
+
+    /// Span of the last consumed token.
+    pub fn prev_span(&self) -> Option<&Span> {
+        match self.pos {
+            0 => None,
+            p => self.tokens.get(p - 1).map(|t| &t.span),
+        }
+    }
+
+    /// Span covering everything consumed since `start` was taken with
+    /// [`Cursor::span`].
+    pub fn span_from(&self, start: &Span) -> Span {
+        let end = match self.prev_span() {
+            Some(s) if s.end > start.start => s.end,
+            _ => start.start,
+        };
+        Span {
+            end,
+            ..start.clone()
+        }
+    }
+}
+
+impl<'l> Iterator for Cursor<'l> {
+    type Item = &'l LexItem;
+
+    fn next(&mut self) -> Option<&'l LexItem> {
+        let t = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(&t.item)
+    }
+}
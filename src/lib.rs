@@ -1,5 +1,20 @@
+pub mod classes;
 pub mod common;
+pub mod config;
+pub mod cst;
+pub mod ddns;
+pub mod dialect;
+pub mod duid;
+pub mod expr;
+pub mod failover;
+pub mod include;
 pub mod leases;
+pub mod leases6;
+pub mod options;
 pub mod parser;
+pub mod resolve;
+pub mod server;
+pub mod writer;
 
+mod edit;
 mod lex;
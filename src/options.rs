@@ -0,0 +1,814 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+
+use crate::common::decode_escaped;
+use crate::common::decode_hex;
+use crate::common::quote;
+use crate::expr::parse_expression;
+use crate::expr::Expression;
+use crate::lex::Cursor;
+use crate::lex::LexItem;
+
+/// Data type of a single option field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionType {
+    IpAddress,
+    Ip6Address,
+    Text,
+    /// Binary data, written as a quoted string or colon-separated hex
+    Data,
+    Boolean,
+    Unsigned8,
+    Unsigned16,
+    Unsigned32,
+    Signed8,
+    Signed16,
+    Signed32,
+    DomainName,
+    /// Comma-separated list of domain names
+    DomainList,
+    /// Options of the named option space, written as data
+    Encapsulate(String),
+}
+
+impl fmt::Display for OptionType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = match self {
+            OptionType::IpAddress => "ip-address",
+            OptionType::Ip6Address => "ip6-address",
+            OptionType::Text => "text",
+            OptionType::Data => "string",
+            OptionType::Boolean => "boolean",
+            OptionType::Unsigned8 => "unsigned integer 8",
+            OptionType::Unsigned16 => "unsigned integer 16",
+            OptionType::Unsigned32 => "unsigned integer 32",
+            OptionType::Signed8 => "signed integer 8",
+            OptionType::Signed16 => "signed integer 16",
+            OptionType::Signed32 => "signed integer 32",
+            OptionType::DomainName => "domain-name",
+            OptionType::DomainList => "domain-list",
+            OptionType::Encapsulate(space) => return write!(f, "encapsulate {}", space),
+        };
+        write!(f, "{}", r)
+    }
+}
+
+/// Layout of an option value: one field or a record of several, optionally
+/// repeated as an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionFormat {
+    pub fields: Vec<OptionType>,
+    pub array: bool,
+}
+
+impl OptionFormat {
+    /// Build a format from the format codes used by ISC dhcpd's option
+    /// tables, e.g. `IA` for an array of IP addresses.
+    fn from_codes(codes: &str) -> OptionFormat {
+        let mut fields = Vec::new();
+        let mut array = false;
+        for c in codes.chars() {
+            fields.push(match c {
+                'I' => OptionType::IpAddress,
+                '6' => OptionType::Ip6Address,
+                't' => OptionType::Text,
+                'X' | 'E' => OptionType::Data,
+                'f' => OptionType::Boolean,
+                'B' => OptionType::Unsigned8,
+                'S' => OptionType::Unsigned16,
+                'L' => OptionType::Unsigned32,
+                'b' => OptionType::Signed8,
+                's' => OptionType::Signed16,
+                'l' => OptionType::Signed32,
+                'd' => OptionType::DomainName,
+                'D' => OptionType::DomainList,
+                'A' => {
+                    array = true;
+                    continue;
+                }
+                _ => unreachable!("unknown option format code {:?}", c),
+            });
+        }
+        OptionFormat { fields, array }
+    }
+}
+
+/// Definition of an option: where it lives, its code and value layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionDefinition {
+    /// Option space, `dhcp` for the standard DHCPv4 options and `dhcp6` for
+    /// DHCPv6.
+    pub space: String,
+    pub name: String,
+    pub code: u32,
+    pub format: OptionFormat,
+}
+
+/// Standard DHCPv4 options: code, name and ISC format codes.
+const DHCP_OPTIONS: &[(u32, &str, &str)] = &[
+    (1, "subnet-mask", "I"),
+    (2, "time-offset", "l"),
+    (3, "routers", "IA"),
+    (4, "time-servers", "IA"),
+    (5, "ien116-name-servers", "IA"),
+    (6, "domain-name-servers", "IA"),
+    (7, "log-servers", "IA"),
+    (8, "cookie-servers", "IA"),
+    (9, "lpr-servers", "IA"),
+    (10, "impress-servers", "IA"),
+    (11, "resource-location-servers", "IA"),
+    (12, "host-name", "t"),
+    (13, "boot-size", "S"),
+    (14, "merit-dump", "t"),
+    (15, "domain-name", "t"),
+    (16, "swap-server", "I"),
+    (17, "root-path", "t"),
+    (18, "extensions-path", "t"),
+    (19, "ip-forwarding", "f"),
+    (20, "non-local-source-routing", "f"),
+    (21, "policy-filter", "IIA"),
+    (22, "max-dgram-reassembly", "S"),
+    (23, "default-ip-ttl", "B"),
+    (24, "path-mtu-aging-timeout", "L"),
+    (25, "path-mtu-plateau-table", "SA"),
+    (26, "interface-mtu", "S"),
+    (27, "all-subnets-local", "f"),
+    (28, "broadcast-address", "I"),
+    (29, "perform-mask-discovery", "f"),
+    (30, "mask-supplier", "f"),
+    (31, "router-discovery", "f"),
+    (32, "router-solicitation-address", "I"),
+    (33, "static-routes", "IIA"),
+    (34, "trailer-encapsulation", "f"),
+    (35, "arp-cache-timeout", "L"),
+    (36, "ieee802-3-encapsulation", "f"),
+    (37, "default-tcp-ttl", "B"),
+    (38, "tcp-keepalive-interval", "L"),
+    (39, "tcp-keepalive-garbage", "f"),
+    (40, "nis-domain", "t"),
+    (41, "nis-servers", "IA"),
+    (42, "ntp-servers", "IA"),
+    (43, "vendor-encapsulated-options", "E"),
+    (44, "netbios-name-servers", "IA"),
+    (45, "netbios-dd-server", "IA"),
+    (46, "netbios-node-type", "B"),
+    (47, "netbios-scope", "t"),
+    (48, "font-servers", "IA"),
+    (49, "x-display-manager", "IA"),
+    (50, "dhcp-requested-address", "I"),
+    (51, "dhcp-lease-time", "L"),
+    (52, "dhcp-option-overload", "B"),
+    (53, "dhcp-message-type", "B"),
+    (54, "dhcp-server-identifier", "I"),
+    (55, "dhcp-parameter-request-list", "BA"),
+    (56, "dhcp-message", "t"),
+    (57, "dhcp-max-message-size", "S"),
+    (58, "dhcp-renewal-time", "L"),
+    (59, "dhcp-rebinding-time", "L"),
+    (60, "vendor-class-identifier", "X"),
+    (61, "dhcp-client-identifier", "X"),
+    (62, "nwip-domain", "X"),
+    (64, "nisplus-domain", "t"),
+    (65, "nisplus-servers", "IA"),
+    (66, "tftp-server-name", "t"),
+    (67, "bootfile-name", "t"),
+    (68, "mobile-ip-home-agent", "IA"),
+    (69, "smtp-server", "IA"),
+    (70, "pop-server", "IA"),
+    (71, "nntp-server", "IA"),
+    (72, "www-server", "IA"),
+    (73, "finger-server", "IA"),
+    (74, "irc-server", "IA"),
+    (75, "streettalk-server", "IA"),
+    (76, "streettalk-directory-assistance-server", "IA"),
+    (77, "user-class", "t"),
+    (82, "relay-agent-information", "E"),
+    (85, "nds-servers", "IA"),
+    (86, "nds-tree-name", "t"),
+    (87, "nds-context", "t"),
+    (88, "bcms-controller-names", "D"),
+    (89, "bcms-controller-address", "IA"),
+    (91, "client-last-transaction-time", "L"),
+    (92, "associated-ip", "IA"),
+    (93, "pxe-system-type", "SA"),
+    (94, "pxe-interface-id", "BBB"),
+    (95, "pxe-client-id", "BX"),
+    (98, "uap-servers", "t"),
+    (100, "pcode", "t"),
+    (101, "tcode", "t"),
+    (108, "v6-only-preferred", "L"),
+    (112, "netinfo-server-address", "IA"),
+    (113, "netinfo-server-tag", "t"),
+    (114, "default-url", "t"),
+    (116, "auto-config", "B"),
+    (117, "name-service-search", "SA"),
+    (118, "subnet-selection", "I"),
+    (119, "domain-search", "D"),
+    (121, "rfc3442-classless-static-routes", "BA"),
+    (145, "forcerenew-nonce-capable", "BA"),
+];
+
+/// Standard DHCPv6 options: code, name and ISC format codes.
+const DHCP6_OPTIONS: &[(u32, &str, &str)] = &[
+    (1, "client-id", "X"),
+    (2, "server-id", "X"),
+    (7, "preference", "B"),
+    (12, "unicast", "6"),
+    (18, "interface-id", "X"),
+    (21, "sip-servers-names", "D"),
+    (22, "sip-servers-addresses", "6A"),
+    (23, "name-servers", "6A"),
+    (24, "domain-search", "D"),
+    (27, "nis-servers", "6A"),
+    (28, "nisp-servers", "6A"),
+    (29, "nis-domain-name", "D"),
+    (30, "nisp-domain-name", "D"),
+    (31, "sntp-servers", "6A"),
+    (32, "info-refresh-time", "L"),
+    (33, "bcms-server-d", "D"),
+    (34, "bcms-server-a", "6A"),
+    (37, "remote-id", "X"),
+    (59, "bootfile-url", "t"),
+    (60, "bootfile-param", "X"),
+    (64, "aftr-name", "d"),
+    (82, "sol-max-rt", "L"),
+    (83, "inf-max-rt", "L"),
+];
+
+/// Relay agent information sub-options.
+const AGENT_OPTIONS: &[(u32, &str, &str)] = &[
+    (1, "circuit-id", "X"),
+    (2, "remote-id", "X"),
+    (4, "DOCSIS-device-class", "L"),
+    (5, "link-selection", "I"),
+];
+
+/// Option definitions known while parsing, looked up by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionRegistry {
+    spaces: Vec<String>,
+    definitions: Vec<OptionDefinition>,
+}
+
+impl Default for OptionRegistry {
+    fn default() -> Self {
+        OptionRegistry::builtin()
+    }
+}
+
+impl OptionRegistry {
+    /// Registry holding the standard DHCPv4, DHCPv6 and relay agent options.
+    pub fn builtin() -> OptionRegistry {
+        let mut spaces = Vec::new();
+        let mut definitions = Vec::new();
+        for (space, table) in &[
+            ("dhcp", DHCP_OPTIONS),
+            ("dhcp6", DHCP6_OPTIONS),
+            ("agent", AGENT_OPTIONS),
+        ] {
+            spaces.push(space.to_string());
+            for (code, name, codes) in table.iter() {
+                definitions.push(OptionDefinition {
+                    space: space.to_string(),
+                    name: name.to_string(),
+                    code: *code,
+                    format: OptionFormat::from_codes(codes),
+                });
+            }
+        }
+        OptionRegistry {
+            spaces,
+            definitions,
+        }
+    }
+
+    pub fn definitions(&self) -> &[OptionDefinition] {
+        &self.definitions
+    }
+
+    pub fn spaces(&self) -> &[String] {
+        &self.spaces
+    }
+
+    pub fn has_space(&self, space: &str) -> bool {
+        self.spaces.iter().any(|s| s == space)
+    }
+
+    /// Declare an option space, as done by `option space name;`.
+    pub fn add_space(&mut self, space: &str) {
+        if !self.has_space(space) {
+            self.spaces.push(space.to_owned());
+        }
+    }
+
+    /// Add a definition. It takes precedence over earlier definitions of the
+    /// same name, including built-in ones.
+    pub fn define(&mut self, definition: OptionDefinition) -> Result<(), String> {
+        if !self.has_space(&definition.space) {
+            return Err(format!("Unknown option space {:?}", definition.space));
+        }
+        self.definitions.push(definition);
+        Ok(())
+    }
+
+    /// Look up an option by name, as written in a statement: either plain
+    /// (`routers`, in the `dhcp` space) or qualified (`dhcp6.name-servers`).
+    pub fn lookup(&self, name: &str) -> Option<&OptionDefinition> {
+        let (space, name) = split_option_name(name);
+        self.definitions
+            .iter()
+            .rev()
+            .find(|d| d.space == space && d.name == name)
+    }
+}
+
+/// Split an option name into its space and the name within the space.
+pub fn split_option_name(name: &str) -> (&str, &str) {
+    match name.split_once('.') {
+        Some((space, name)) => (space, name),
+        None => ("dhcp", name),
+    }
+}
+
+/// Typed value of an option statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionValue {
+    Ip(Ipv4Addr),
+    /// An IP address field given as a host name, resolved by dhcpd.
+    Hostname(String),
+    Ip6(Ipv6Addr),
+    Text(String),
+    Data(Vec<u8>),
+    Boolean(bool),
+    Unsigned(u32),
+    Signed(i32),
+    DomainName(String),
+    DomainList(Vec<String>),
+    /// Fields of a multi-field option.
+    Record(Vec<OptionValue>),
+    Array(Vec<OptionValue>),
+    /// Value of an option missing from the registry: the tokens as written.
+    Unknown(Vec<String>),
+    /// Value computed by dhcpd, from `option name = expression;`.
+    Expression(Expression),
+}
+
+fn join<T: fmt::Display>(items: &[T], sep: &str) -> String {
+    items
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>()
+        .join(sep)
+}
+
+impl fmt::Display for OptionValue {
+    /// Writes the value in dhcpd.conf syntax.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptionValue::Ip(v) => write!(f, "{}", v),
+            OptionValue::Hostname(v) => write!(f, "{}", v),
+            OptionValue::Ip6(v) => write!(f, "{}", v),
+            OptionValue::Text(v) | OptionValue::DomainName(v) => write!(f, "{}", quote(v)),
+            OptionValue::Data(v) => write!(
+                f,
+                "{}",
+                v.iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<String>>()
+                    .join(":")
+            ),
+            OptionValue::Boolean(v) => write!(f, "{}", v),
+            OptionValue::Unsigned(v) => write!(f, "{}", v),
+            OptionValue::Signed(v) => write!(f, "{}", v),
+            OptionValue::DomainList(v) => write!(
+                f,
+                "{}",
+                v.iter().map(quote).collect::<Vec<String>>().join(", ")
+            ),
+            OptionValue::Record(v) => write!(f, "{}", join(v, " ")),
+            OptionValue::Array(v) => write!(f, "{}", join(v, ", ")),
+            OptionValue::Unknown(v) => write!(f, "{}", v.join(" ")),
+            OptionValue::Expression(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// `option name value;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionStatement {
+    /// Option space, `dhcp` unless the name was qualified.
+    pub space: String,
+    pub name: String,
+    /// Option code, if the option is defined.
+    pub code: Option<u32>,
+    pub value: OptionValue,
+}
+
+impl OptionStatement {
+    /// Name as written in dhcpd.conf: qualified with the space unless it is
+    /// the `dhcp` space.
+    pub fn full_name(&self) -> String {
+        if self.space == "dhcp" {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.space, self.name)
+        }
+    }
+}
+
+impl fmt::Display for OptionStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            OptionValue::Expression(v) => write!(f, "option {} = {};", self.full_name(), v),
+            v => write!(f, "option {} {};", self.full_name(), v),
+        }
+    }
+}
+
+fn parse_integer<T: std::str::FromStr>(
+    item: Option<&LexItem>,
+    ty: &OptionType,
+) -> Result<T, String> {
+    match item {
+        Some(LexItem::Word(w)) => w
+            .parse::<T>()
+            .map_err(|_| format!("{:?} is not a valid {}", w, ty)),
+        other => Err(format!("Expected {}, got {:?}", ty, other)),
+    }
+}
+
+/// Parse a value of a single field.
+fn parse_field(it: &mut Cursor, ty: &OptionType) -> Result<OptionValue, String> {
+    let item = it.next();
+    let value = match ty {
+        OptionType::IpAddress => match item {
+            Some(LexItem::Word(w)) => match w.parse::<Ipv4Addr>() {
+                Ok(ip) => OptionValue::Ip(ip),
+                Err(_) if w.chars().any(|c| c.is_ascii_alphabetic()) => {
+                    OptionValue::Hostname(w.clone())
+                }
+                Err(_) => return Err(format!("{:?} is not a valid IP address", w)),
+            },
+            other => return Err(format!("Expected IP address, got {:?}", other)),
+        },
+        OptionType::Ip6Address => match item {
+            Some(LexItem::Word(w)) => OptionValue::Ip6(
+                w.parse::<Ipv6Addr>()
+                    .map_err(|_| format!("{:?} is not a valid IPv6 address", w))?,
+            ),
+            other => return Err(format!("Expected IPv6 address, got {:?}", other)),
+        },
+        OptionType::Text => match item {
+            Some(LexItem::Str(w)) | Some(LexItem::Word(w)) => OptionValue::Text(w.clone()),
+            other => return Err(format!("Expected text, got {:?}", other)),
+        },
+        OptionType::DomainName => match item {
+            Some(LexItem::Str(w)) | Some(LexItem::Word(w)) => OptionValue::DomainName(w.clone()),
+            other => return Err(format!("Expected domain name, got {:?}", other)),
+        },
+        OptionType::DomainList => {
+            let mut names = Vec::new();
+            let mut item = item;
+            loop {
+                match item {
+                    Some(LexItem::Str(w)) | Some(LexItem::Word(w)) => names.push(w.clone()),
+                    other => return Err(format!("Expected domain name, got {:?}", other)),
+                }
+                if it.peek() != Some(&&LexItem::Comma) {
+                    break;
+                }
+                it.next();
+                item = it.next();
+            }
+            OptionValue::DomainList(names)
+        }
+        OptionType::Data | OptionType::Encapsulate(_) => match item {
+            Some(LexItem::Str(w)) => OptionValue::Data(decode_escaped(w)?),
+            Some(LexItem::Word(w)) => OptionValue::Data(decode_hex(w)?),
+            other => return Err(format!("Expected data string, got {:?}", other)),
+        },
+        OptionType::Boolean => match item {
+            Some(LexItem::Word(w)) => match w.as_str() {
+                "true" | "on" => OptionValue::Boolean(true),
+                "false" | "off" => OptionValue::Boolean(false),
+                _ => return Err(format!("{:?} is not a valid boolean", w)),
+            },
+            other => return Err(format!("Expected boolean, got {:?}", other)),
+        },
+        OptionType::Unsigned8 => OptionValue::Unsigned(parse_integer::<u8>(item, ty)?.into()),
+        OptionType::Unsigned16 => OptionValue::Unsigned(parse_integer::<u16>(item, ty)?.into()),
+        OptionType::Unsigned32 => OptionValue::Unsigned(parse_integer::<u32>(item, ty)?),
+        OptionType::Signed8 => OptionValue::Signed(parse_integer::<i8>(item, ty)?.into()),
+        OptionType::Signed16 => OptionValue::Signed(parse_integer::<i16>(item, ty)?.into()),
+        OptionType::Signed32 => OptionValue::Signed(parse_integer::<i32>(item, ty)?),
+    };
+
+    Ok(value)
+}
+
+/// Parse an option value of the given format, leaving the terminating `;` in
+/// place.
+pub(crate) fn parse_option_value(
+    it: &mut Cursor,
+    format: &OptionFormat,
+) -> Result<OptionValue, String> {
+    let mut values = Vec::new();
+    loop {
+        let mut fields = Vec::new();
+        for ty in &format.fields {
+            fields.push(parse_field(it, ty)?);
+        }
+        values.push(match fields.len() {
+            1 => fields.remove(0),
+            _ => OptionValue::Record(fields),
+        });
+
+        if !format.array || it.peek() != Some(&&LexItem::Comma) {
+            break;
+        }
+        it.next();
+    }
+
+    if format.array {
+        Ok(OptionValue::Array(values))
+    } else {
+        Ok(values.remove(0))
+    }
+}
+
+/// Parse an `option` statement starting at its keyword, including the
+/// terminating `;`.
+pub(crate) fn parse_option(
+    it: &mut Cursor,
+    registry: &OptionRegistry,
+    strict: bool,
+) -> Result<OptionStatement, String> {
+    it.next(); // "option"
+    parse_option_body(it, registry, strict)
+}
+
+/// Parse the value of an option as the tokens written, up to the `;`.
+fn parse_raw_value(it: &mut Cursor, full_name: &str) -> Result<Vec<String>, String> {
+    let mut raw = Vec::new();
+    loop {
+        match it.peek().copied() {
+            None | Some(LexItem::Endl) => return Ok(raw),
+            Some(LexItem::Paren('{')) | Some(LexItem::Paren('}')) => {
+                return Err(format!("Unexpected brace in option {}", full_name))
+            }
+            Some(t) => raw.push(t.raw()),
+        }
+        it.next();
+    }
+}
+
+/// Parse an option name and value, e.g. following `option` or
+/// `host-identifier option`, including the terminating `;`. Unless `strict`,
+/// a value not matching the definition of the option is kept as
+/// [`OptionValue::Unknown`].
+pub(crate) fn parse_option_body(
+    it: &mut Cursor,
+    registry: &OptionRegistry,
+    strict: bool,
+) -> Result<OptionStatement, String> {
+    let full_name = match it.next() {
+        Some(LexItem::Word(w)) => w.clone(),
+        other => return Err(format!("Expected option name, got {:?}", other)),
+    };
+    let (space, name) = split_option_name(&full_name);
+
+    let definition = registry.lookup(&full_name);
+    let code = definition.map(|def| def.code);
+    let value = match definition {
+        _ if matches!(it.peek(), Some(LexItem::Word(w)) if w == "=") => {
+            it.next();
+            OptionValue::Expression(parse_expression(it)?)
+        }
+        Some(def) => {
+            let start = it.clone();
+            match parse_option_value(it, &def.format) {
+                Ok(value) if it.peek() == Some(&&LexItem::Endl) => value,
+                _ if !strict => {
+                    *it = start;
+                    OptionValue::Unknown(parse_raw_value(it, &full_name)?)
+                }
+                // The missing ';' is reported below.
+                Ok(value) => value,
+                Err(err) => return Err(format!("Option {}: {}", full_name, err)),
+            }
+        }
+        None => OptionValue::Unknown(parse_raw_value(it, &full_name)?),
+    };
+
+    match it.next() {
+        Some(LexItem::Endl) => {}
+        other => {
+            return Err(format!(
+                "Expected ';' after option {}, got {:?}",
+                full_name, other
+            ))
+        }
+    }
+
+    Ok(OptionStatement {
+        space: space.to_owned(),
+        name: name.to_owned(),
+        code,
+        value,
+    })
+}
+
+/// `option space name;`, declaring a space for vendor or site options.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OptionSpace {
+    pub name: String,
+    /// Width in bytes of option codes, from `code width N`.
+    pub code_width: Option<u8>,
+    /// Width in bytes of option lengths, from `length width N`.
+    pub length_width: Option<u8>,
+    /// From `hash size N`.
+    pub hash_size: Option<u32>,
+}
+
+fn expect_word(it: &mut Cursor, word: &str, after: &str) -> Result<(), String> {
+    match it.next() {
+        Some(LexItem::Word(w)) if w == word => Ok(()),
+        other => Err(format!(
+            "Expected '{}' after {}, got {:?}",
+            word, after, other
+        )),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(it: &mut Cursor, what: &str) -> Result<T, String> {
+    match it.next() {
+        Some(LexItem::Word(w)) => w
+            .parse::<T>()
+            .map_err(|_| format!("Invalid {} {:?}", what, w)),
+        other => Err(format!("Expected {}, got {:?}", what, other)),
+    }
+}
+
+/// Whether the tokens ahead are `option space name`.
+pub(crate) fn is_option_space(it: &Cursor) -> bool {
+    let mut ahead = it.clone();
+    matches!(ahead.nth(1), Some(LexItem::Word(w)) if w == "space")
+}
+
+/// Whether the tokens ahead are `option name code N = type`.
+pub(crate) fn is_option_definition(it: &Cursor) -> bool {
+    let mut ahead = it.clone();
+    matches!(ahead.nth(2), Some(LexItem::Word(w)) if w == "code")
+}
+
+/// Parse an `option space` statement starting at its keyword, including the
+/// terminating `;`, and declare the space in `registry`.
+pub(crate) fn parse_option_space(
+    it: &mut Cursor,
+    registry: &mut OptionRegistry,
+) -> Result<OptionSpace, String> {
+    it.next(); // "option"
+    it.next(); // "space"
+    let mut space = OptionSpace {
+        name: match it.next() {
+            Some(LexItem::Word(w)) => w.clone(),
+            other => return Err(format!("Expected option space name, got {:?}", other)),
+        },
+        ..OptionSpace::default()
+    };
+
+    loop {
+        match it.next() {
+            Some(LexItem::Endl) => break,
+            Some(LexItem::Word(w)) if w == "code" => {
+                expect_word(it, "width", "code")?;
+                space.code_width = Some(parse_number(it, "code width")?);
+            }
+            Some(LexItem::Word(w)) if w == "length" => {
+                expect_word(it, "width", "length")?;
+                space.length_width = Some(parse_number(it, "length width")?);
+            }
+            Some(LexItem::Word(w)) if w == "hash" => {
+                expect_word(it, "size", "hash")?;
+                space.hash_size = Some(parse_number(it, "hash size")?);
+            }
+            other => {
+                return Err(format!(
+                    "Unexpected {:?} in option space {}",
+                    other, space.name
+                ))
+            }
+        }
+    }
+
+    registry.add_space(&space.name);
+    Ok(space)
+}
+
+/// Parse a single type of an option definition, e.g. `unsigned integer 16`.
+fn parse_option_type(it: &mut Cursor) -> Result<OptionType, String> {
+    let word = match it.next() {
+        Some(LexItem::Word(w)) => w.as_str(),
+        other => return Err(format!("Expected option type, got {:?}", other)),
+    };
+
+    let ty = match word {
+        "ip-address" => OptionType::IpAddress,
+        "ip6-address" => OptionType::Ip6Address,
+        "text" => OptionType::Text,
+        "string" => OptionType::Data,
+        "boolean" => OptionType::Boolean,
+        "domain-name" => OptionType::DomainName,
+        "domain-list" => {
+            if let Some(LexItem::Word(w)) = it.peek() {
+                if w == "compressed" {
+                    it.next();
+                }
+            }
+            OptionType::DomainList
+        }
+        "encapsulate" => match it.next() {
+            Some(LexItem::Word(w)) => OptionType::Encapsulate(w.clone()),
+            other => return Err(format!("Expected option space name, got {:?}", other)),
+        },
+        "unsigned" | "signed" | "integer" => {
+            if word != "integer" {
+                expect_word(it, "integer", word)?;
+            }
+            let width = parse_number::<u8>(it, "integer width")?;
+            match (word == "unsigned", width) {
+                (true, 8) => OptionType::Unsigned8,
+                (true, 16) => OptionType::Unsigned16,
+                (true, 32) => OptionType::Unsigned32,
+                (false, 8) => OptionType::Signed8,
+                (false, 16) => OptionType::Signed16,
+                (false, 32) => OptionType::Signed32,
+                _ => return Err(format!("Invalid integer width {}", width)),
+            }
+        }
+        _ => return Err(format!("Unknown option type {:?}", word)),
+    };
+
+    Ok(ty)
+}
+
+/// Parse the format of an option definition: a type, a `{ type, ... }`
+/// record, or an `array of` either.
+fn parse_option_format(it: &mut Cursor) -> Result<OptionFormat, String> {
+    let array = match it.peek() {
+        Some(LexItem::Word(w)) if w == "array" => {
+            it.next();
+            expect_word(it, "of", "array")?;
+            true
+        }
+        _ => false,
+    };
+
+    let mut fields = Vec::new();
+    if it.peek() == Some(&&LexItem::Paren('{')) {
+        it.next();
+        loop {
+            fields.push(parse_option_type(it)?);
+            match it.next() {
+                Some(LexItem::Comma) => {}
+                Some(LexItem::Paren('}')) => break,
+                other => return Err(format!("Expected ',' or '}}', got {:?}", other)),
+            }
+        }
+    } else {
+        fields.push(parse_option_type(it)?);
+    }
+
+    Ok(OptionFormat { fields, array })
+}
+
+/// Parse an `option name code N = type;` definition starting at its keyword,
+/// including the terminating `;`, and add it to `registry`.
+pub(crate) fn parse_option_definition(
+    it: &mut Cursor,
+    registry: &mut OptionRegistry,
+) -> Result<OptionDefinition, String> {
+    it.next(); // "option"
+    let full_name = match it.next() {
+        Some(LexItem::Word(w)) => w.clone(),
+        other => return Err(format!("Expected option name, got {:?}", other)),
+    };
+    let (space, name) = split_option_name(&full_name);
+    it.next(); // "code"
+    let code = parse_number::<u32>(it, "option code")?;
+    expect_word(it, "=", "option code")?;
+    let format = parse_option_format(it)?;
+    if it.next() != Some(&LexItem::Endl) {
+        return Err(format!("Expected ';' after definition of {}", full_name));
+    }
+
+    let definition = OptionDefinition {
+        space: space.to_owned(),
+        name: name.to_owned(),
+        code,
+        format,
+    };
+    registry.define(definition.clone())?;
+    Ok(definition)
+}
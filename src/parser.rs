@@ -1,31 +1,187 @@
-use std::iter::Peekable;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
 
-use crate::leases::parse_lease;
+use crate::classes::parse_class;
+use crate::classes::parse_subclass;
+use crate::classes::ClassDecl;
+use crate::classes::SubclassDecl;
+use crate::common::decode_escaped;
+use crate::common::decode_hex;
+use crate::config::parse_group;
+use crate::config::parse_if;
+use crate::config::parse_on;
+use crate::config::parse_parameter;
+use crate::config::parse_pool;
+use crate::config::parse_prefix6;
+use crate::config::parse_range;
+use crate::config::parse_range6;
+use crate::config::parse_set;
+use crate::config::parse_shared_network;
+use crate::config::parse_subnet;
+use crate::config::parse_subnet6;
+use crate::config::parse_unset;
+use crate::config::prefix_mask6;
+use crate::config::Config;
+use crate::config::Scope;
+use crate::config::ScopeEntry;
+use crate::config::ScopePath;
+use crate::config::Statement;
+use crate::config::StatementKind;
+use crate::config::Unknown;
+use crate::ddns::parse_key;
+use crate::ddns::parse_zone;
+use crate::dialect::Dialect;
+use crate::dialect::DialectState;
+use crate::duid::Duid;
+use crate::failover::parse_failover_peer;
+use crate::failover::parse_failover_peer_state;
+use crate::failover::FailoverPeerState;
+use crate::include::parse_include;
+use crate::include::parse_included;
+use crate::include::FileLoader;
+use crate::leases::parse_lease_entries;
 use crate::leases::Lease;
 use crate::leases::LeaseKeyword;
 use crate::leases::Leases;
 pub use crate::leases::LeasesMethods;
+use crate::leases6::parse_ia;
+use crate::leases6::parse_prefix;
+use crate::leases6::ByteOrder;
+use crate::leases6::Ia;
 use crate::lex::lex;
+use crate::lex::Cursor;
 use crate::lex::LexItem;
+use crate::lex::Token;
+use crate::options::is_option_definition;
+use crate::options::is_option_space;
+use crate::options::parse_option;
+use crate::options::parse_option_body;
+use crate::options::parse_option_definition;
+use crate::options::parse_option_space;
+use crate::options::OptionRegistry;
+use crate::options::OptionStatement;
+use crate::options::OptionValue;
+use crate::server::ServerParameters;
 
 /// A `host` declaration (static reservation), e.g.
 /// `host name { hardware ethernet aa:bb:..; fixed-address 10.0.0.1; }`.
 ///
 /// Host declarations appear both in `dhcpd.conf` and — when created via OMAPI —
 /// in `dhcpd.leases`, so the parser must extract them without choking.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Host {
     pub name: String,
+    /// Type from `hardware TYPE mac;`, e.g. `ethernet`.
+    pub hardware_type: Option<String>,
     pub mac: Option<String>,
+    /// Client identifier from `uid`.
+    pub uid: Option<Vec<u8>>,
+    pub host_identifier: Option<HostIdentifier>,
     /// `fixed-address` may list several addresses; dhcpd picks the one on the
     /// matching subnet.
     pub fixed_addresses: Vec<String>,
+    pub fixed_addresses6: Vec<Ipv6Addr>,
+    /// Prefix and prefix length from `fixed-prefix6`.
+    pub fixed_prefix6: Option<(Ipv6Addr, u8)>,
+    pub ddns_hostname: Option<String>,
+    pub filename: Option<String>,
+    pub next_server: Option<String>,
+    /// The host was created through OMAPI rather than declared in dhcpd.conf.
+    pub dynamic: bool,
+    /// The host was removed through OMAPI; see [`ParserResult::live_hosts`].
+    pub deleted: bool,
+    /// Options, parameters and other statements of the host scope.
+    pub statements: Vec<Statement>,
+    /// Declarations the host is nested in.
+    pub scope: ScopePath,
+}
+
+/// `host-identifier option name value;`: identifies the host by an option
+/// sent by the client instead of its hardware address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostIdentifier {
+    pub option: OptionStatement,
+    /// Relay hop to take the option from, for `host-identifier v6relopt N`.
+    pub v6relopt: Option<u32>,
+}
+
+impl Host {
+    /// The `fixed-address` that lies on the subnet the host is declared in,
+    /// i.e. the one dhcpd hands out there.
+    pub fn fixed_address_on_subnet(&self) -> Option<Ipv4Addr> {
+        let (network, prefix_len) = self.scope.subnet()?;
+        let mask = u32::MAX
+            .checked_shl(32 - u32::from(prefix_len))
+            .unwrap_or(0);
+        self.fixed_addresses
+            .iter()
+            .filter_map(|a| a.parse::<Ipv4Addr>().ok())
+            .find(|a| u32::from(*a) & mask == u32::from(network))
+    }
+
+    /// The IPv6 fixed address on the subnet6 enclosing the host declaration.
+    pub fn fixed_address6_on_subnet(&self) -> Option<Ipv6Addr> {
+        let (network, prefix_len) = self.scope.subnet6()?;
+        let mask = prefix_mask6(prefix_len);
+        self.fixed_addresses6
+            .iter()
+            .copied()
+            .find(|a| u128::from(*a) & mask == u128::from(network))
+    }
+
+    /// Value of `option host-name` in the host scope.
+    pub fn host_name(&self) -> Option<&str> {
+        match self.option("host-name").map(|o| &o.value) {
+            Some(OptionValue::Text(v)) => Some(v),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParserResult {
     pub leases: Leases,
     pub hosts: Vec<Host>,
+    /// DHCPv6 identity associations (`ia-na`, `ia-ta`, `ia-pd`) from
+    /// `dhcpd6.leases`.
+    pub ias: Vec<Ia>,
+    /// Value of the `authoring-byte-order` entry, if the file has one.
+    pub authoring_byte_order: Option<ByteOrder>,
+    /// DUID the server identifies itself with (`server-duid`).
+    pub server_duid: Option<Duid>,
+    /// Persisted failover states (`failover peer "name" state { ... }`).
+    pub failover_peers: Vec<FailoverPeerState>,
+    /// Classes recorded in the leases file, e.g. ones created through OMAPI.
+    pub classes: Vec<ClassDecl>,
+    /// Subclasses recorded in the leases file, e.g. ones spawned by a class
+    /// with `spawn with`.
+    pub subclasses: Vec<SubclassDecl>,
+    /// Dialect the input was parsed as, see [`Config::dialect`].
+    pub dialect: Option<Dialect>,
+}
+
+impl ParserResult {
+    /// Hosts in effect after applying every host entry in file order, the way
+    /// dhcpd does when it reads `dhcpd.leases`: a later entry for the same
+    /// host name replaces the earlier one and an entry marked `deleted`
+    /// removes it.
+    pub fn live_hosts(&self) -> Vec<Host> {
+        let mut result: Vec<Host> = Vec::new();
+
+        for host in &self.hosts {
+            let existing = result.iter().position(|h| h.name == host.name);
+            match (existing, host.deleted) {
+                (Some(i), true) => {
+                    result.remove(i);
+                }
+                (None, true) => {}
+                (Some(i), false) => result[i] = host.clone(),
+                (None, false) => result.push(host.clone()),
+            }
+        }
+
+        result
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -52,10 +208,7 @@ impl ConfigKeyword {
 
 /// Consume tokens until (and including) the matching `}` of an already-opened
 /// block, honoring nesting. Used to skip declarations we don't model.
-fn skip_braces<'l, T>(it: &mut Peekable<T>) -> Result<(), String>
-where
-    T: Iterator<Item = &'l LexItem>,
-{
+pub(crate) fn skip_braces<'l>(it: &mut Cursor<'l>) -> Result<(), String> {
     let mut depth = 1usize;
     while depth > 0 {
         match it.next() {
@@ -68,15 +221,112 @@ where
     Ok(())
 }
 
-/// Skip a single unknown statement inside a `host` block: either up to the
-/// terminating `;` or over a nested `{...}` block.
-fn skip_host_statement<'l, T>(it: &mut Peekable<T>) -> Result<(), String>
-where
-    T: Iterator<Item = &'l LexItem>,
-{
+/// Whether the statement ahead opens a block rather than ending with `;`.
+fn is_block(it: &Cursor) -> bool {
+    let mut ahead = it.clone();
+    loop {
+        match ahead.next() {
+            None | Some(LexItem::Endl) | Some(LexItem::Paren('}')) => return false,
+            Some(LexItem::Paren('{')) => return true,
+            Some(_) => {}
+        }
+    }
+}
+
+/// Begin an unknown statement at the current token, taking its keyword.
+fn start_unknown(it: &mut Cursor) -> Unknown {
+    let span = it.span();
+    let keyword = match it.peek() {
+        Some(LexItem::Paren('{')) | None => String::new(),
+        Some(_) => it.next().map(|t| t.raw()).unwrap_or_default(),
+    };
+    Unknown {
+        keyword,
+        tokens: Vec::new(),
+        children: None,
+        span,
+    }
+}
+
+/// Parse a statement the library doesn't model as an [`Unknown`] node:
+/// either up to the terminating `;` or including its `{...}` block. Stops
+/// in front of the `}` closing the enclosing block. The statement is at
+/// nesting level `depth`, and its block may nest up to `max_depth` levels.
+///
+/// Works without recursion, so that nesting is only bounded by `max_depth`.
+pub(crate) fn parse_unknown(
+    it: &mut Cursor,
+    depth: usize,
+    max_depth: Option<usize>,
+) -> Result<Unknown, String> {
+    // Blocks opened and not closed yet, innermost last.
+    let mut open: Vec<Unknown> = Vec::new();
+    let mut node = start_unknown(it);
     loop {
         match it.peek().copied() {
-            None => return Err("Unexpected EOF inside host block".to_owned()),
+            None => return Err("Unexpected EOF inside block".to_owned()),
+            Some(LexItem::Paren('{')) => {
+                it.next();
+                node.children = Some(Vec::new());
+                open.push(node);
+            }
+            // A `}` closing the enclosing block ends a statement missing
+            // its `;`.
+            Some(LexItem::Endl) | Some(LexItem::Paren('}')) => {
+                if it.peek() == Some(&&LexItem::Endl) {
+                    it.next();
+                }
+                node.span = it.span_from(&node.span);
+                match open.last_mut().and_then(|b| b.children.as_mut()) {
+                    Some(children) => children.push(node),
+                    None => return Ok(node),
+                }
+            }
+            Some(t) => {
+                node.tokens.push(t.raw());
+                it.next();
+                continue;
+            }
+        }
+
+        // Inside the innermost open block: close it, or start its next
+        // statement.
+        loop {
+            match it.peek().copied() {
+                None => return Err("Unexpected EOF inside block".to_owned()),
+                Some(LexItem::Endl) => {
+                    it.next();
+                }
+                Some(LexItem::Paren('}')) => {
+                    it.next();
+                    let mut block = open.pop().expect("unknown block is open");
+                    block.span = it.span_from(&block.span);
+                    match open.last_mut().and_then(|b| b.children.as_mut()) {
+                        Some(children) => children.push(block),
+                        None => return Ok(block),
+                    }
+                }
+                Some(_) => {
+                    if let Some(max) = max_depth {
+                        if depth + open.len() > max {
+                            return Err(format!("Nesting deeper than {} levels", max));
+                        }
+                    }
+                    node = start_unknown(it);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Skip a single unknown statement inside a block: either up to the
+/// terminating `;` or over a nested `{...}` block. Stops in front of the `}`
+/// closing the enclosing block.
+pub(crate) fn skip_statement<'l>(it: &mut Cursor<'l>) -> Result<(), String> {
+    loop {
+        match it.peek().copied() {
+            None => return Err("Unexpected EOF inside block".to_owned()),
             Some(LexItem::Endl) => {
                 it.next();
                 return Ok(());
@@ -93,13 +343,57 @@ where
     }
 }
 
-fn parse_host<'l, T>(it: &mut Peekable<T>) -> Result<Host, String>
-where
-    T: Iterator<Item = &'l LexItem>,
-{
+/// Whether the tokens ahead are a keyword followed by a single value and `;`,
+/// as opposed to e.g. `ddns-hostname = expression;`.
+fn is_simple_value(it: &Cursor) -> bool {
+    let mut ahead = it.clone();
+    matches!(
+        (ahead.nth(1), ahead.next()),
+        (
+            Some(LexItem::Word(_)) | Some(LexItem::Str(_)),
+            Some(LexItem::Endl)
+        )
+    )
+}
+
+/// Parse the value of a `keyword value;` statement, including the `;`.
+fn parse_simple_value(it: &mut Cursor) -> Result<String, String> {
+    it.next(); // keyword
+    let value = match it.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => w.clone(),
+        other => return Err(format!("Expected value, got {:?}", other)),
+    };
+    it.next(); // ";"
+    Ok(value)
+}
+
+fn parse_host_identifier(it: &mut Cursor, ctx: &mut Context) -> Result<HostIdentifier, String> {
+    it.next(); // "host-identifier"
+    let v6relopt = match it.next() {
+        Some(LexItem::Word(w)) if w.as_str() == "option" => None,
+        Some(LexItem::Word(w)) if w.as_str() == "v6relopt" => match it.next() {
+            Some(LexItem::Word(n)) => Some(
+                n.parse::<u32>()
+                    .map_err(|_| format!("Invalid relay hop {:?}", n))?,
+            ),
+            other => return Err(format!("Expected relay hop, got {:?}", other)),
+        },
+        other => {
+            return Err(format!(
+                "Expected 'option' or 'v6relopt' after host-identifier, got {:?}",
+                other
+            ))
+        }
+    };
+    let option = parse_option_body(it, &ctx.options, ctx.settings.strict_options)?;
+
+    Ok(HostIdentifier { option, v6relopt })
+}
+
+fn parse_host(it: &mut Cursor, ctx: &mut Context) -> Result<Host, String> {
     it.next(); // "host"
     let name = match it.next() {
-        Some(LexItem::Word(w)) => w.clone(),
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => w.clone(),
         other => return Err(format!("Expected host name, got {:?}", other)),
     };
     match it.next() {
@@ -107,8 +401,13 @@ where
         other => return Err(format!("Expected '{{' after host name, got {:?}", other)),
     }
 
-    let mut mac = None;
-    let mut fixed_addresses = Vec::new();
+    let mut host = Host {
+        name,
+        scope: ScopePath {
+            entries: ctx.scope.clone(),
+        },
+        ..Host::default()
+    };
 
     loop {
         match it.peek().copied() {
@@ -119,9 +418,12 @@ where
             }
             Some(LexItem::Opt(LeaseKeyword::Hardware)) => {
                 it.next(); // "hardware"
-                it.next(); // hardware type ("ethernet")
+                if let Some(LexItem::Word(t)) = it.peek().copied() {
+                    host.hardware_type = Some(t.clone());
+                    it.next();
+                }
                 if let Some(LexItem::Word(m)) = it.peek().copied() {
-                    mac = Some(m.to_lowercase());
+                    host.mac = Some(m.to_lowercase());
                     it.next();
                 }
                 // Consume the rest up to the terminating ';'.
@@ -133,6 +435,17 @@ where
                     }
                 }
             }
+            Some(LexItem::Opt(LeaseKeyword::Uid)) => {
+                it.next();
+                host.uid = Some(match it.next() {
+                    Some(LexItem::Str(v)) => decode_escaped(v)?,
+                    Some(LexItem::Word(v)) => decode_hex(v)?,
+                    other => return Err(format!("Expected uid, got {:?}", other)),
+                });
+                if it.next() != Some(&LexItem::Endl) {
+                    return Err("Expected ';' after uid".to_owned());
+                }
+            }
             Some(LexItem::Word(w)) if w.as_str() == "fixed-address" => {
                 it.next();
                 loop {
@@ -143,35 +456,68 @@ where
                             break;
                         }
                         Some(LexItem::Word(x)) => {
-                            for part in x.split(',') {
-                                let p = part.trim();
-                                if !p.is_empty() {
-                                    fixed_addresses.push(p.to_owned());
-                                }
-                            }
+                            host.fixed_addresses.push(x.clone());
                             it.next();
                         }
+                        // Commas between the addresses.
                         Some(_) => {
                             it.next();
                         }
                     }
                 }
             }
-            Some(_) => skip_host_statement(it)?,
+            Some(LexItem::Word(w)) if w.as_str() == "fixed-address6" => {
+                it.next();
+                loop {
+                    match it.next() {
+                        Some(LexItem::Word(x)) => host.fixed_addresses6.push(
+                            x.parse::<Ipv6Addr>()
+                                .map_err(|_| format!("Invalid IPv6 address {:?}", x))?,
+                        ),
+                        other => return Err(format!("Expected IPv6 address, got {:?}", other)),
+                    }
+                    match it.next() {
+                        Some(LexItem::Comma) => {}
+                        Some(LexItem::Endl) => break,
+                        other => return Err(format!("Expected ',' or ';', got {:?}", other)),
+                    }
+                }
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "fixed-prefix6" => {
+                host.fixed_prefix6 = Some(parse_prefix(&parse_simple_value(it)?)?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "host-identifier" => {
+                host.host_identifier = Some(parse_host_identifier(it, ctx)?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "ddns-hostname" && is_simple_value(it) => {
+                host.ddns_hostname = Some(parse_simple_value(it)?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "filename" && is_simple_value(it) => {
+                host.filename = Some(parse_simple_value(it)?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "next-server" && is_simple_value(it) => {
+                host.next_server = Some(parse_simple_value(it)?);
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "dynamic" => {
+                host.dynamic = true;
+                skip_statement(it)?;
+            }
+            Some(LexItem::Word(w)) if w.as_str() == "deleted" => {
+                host.deleted = true;
+                skip_statement(it)?;
+            }
+            Some(_) => {
+                if let Some(statement) = parse_statement(it, ctx)? {
+                    host.statements.push(statement);
+                }
+            }
         }
     }
 
-    Ok(Host {
-        name,
-        mac,
-        fixed_addresses,
-    })
+    Ok(host)
 }
 
-fn parse_lease_decl<'l, T>(it: &mut Peekable<T>, leases: &mut Leases) -> Result<(), String>
-where
-    T: Iterator<Item = &'l LexItem>,
-{
+fn parse_lease_decl(it: &mut Cursor, ctx: &mut Context) -> Result<Lease, String> {
     it.next(); // "lease"
     let ip = match it.next() {
         Some(v) => v.to_string(),
@@ -186,7 +532,7 @@ where
         ip,
         ..Lease::default()
     };
-    parse_lease(&mut lease, it)?;
+    parse_lease_entries(&mut lease, it, ctx)?;
 
     match it.peek().copied() {
         Some(LexItem::Paren('}')) => {
@@ -197,86 +543,568 @@ where
         }
     }
 
-    leases.push(lease);
-    Ok(())
+    Ok(lease)
+}
+
+/// Parse an `authoring-byte-order` entry, leaving the terminating `;` in place.
+fn parse_authoring_byte_order<'l>(it: &mut Cursor<'l>) -> Result<ByteOrder, String> {
+    it.next(); // "authoring-byte-order"
+    match it.next() {
+        Some(LexItem::Word(w)) => ByteOrder::from(w),
+        other => Err(format!("Expected byte order, got {:?}", other)),
+    }
+}
+
+/// Parse a `server-duid` entry of the leases file, leaving the terminating `;`
+/// in place.
+fn parse_server_duid<'l>(it: &mut Cursor<'l>) -> Result<Duid, String> {
+    it.next(); // "server-duid"
+    match it.next() {
+        Some(LexItem::Word(w)) | Some(LexItem::Str(w)) => Ok(Duid::from_bytes(&decode_escaped(w)?)),
+        other => Err(format!("Expected server DUID, got {:?}", other)),
+    }
+}
+
+/// Whether the tokens ahead are `failover peer "name" state`, as opposed to the
+/// `failover peer "name" { ... }` declaration of dhcpd.conf.
+fn is_failover_peer_state<'l>(it: &Cursor<'l>) -> bool {
+    let mut ahead = it.clone();
+    ahead.nth(3) == Some(&LexItem::Opt(LeaseKeyword::State))
+}
+
+/// State carried through the parsing of one input.
+#[derive(Default)]
+pub(crate) struct Context<'a> {
+    /// Loader for `include` statements, which are not followed without one.
+    pub(crate) loader: Option<&'a dyn FileLoader>,
+    /// Canonical names of the files being included, outermost first, to
+    /// detect cycles.
+    pub(crate) include_stack: Vec<String>,
+    /// Names of the failover peers declared so far, checked against pool
+    /// references when includes are followed.
+    pub(crate) failover_peers: Vec<String>,
+    /// Declarations enclosing the statement being parsed.
+    pub(crate) scope: Vec<ScopeEntry>,
+    /// Set once an error message carries its location, so that it is not
+    /// prefixed again by the including files.
+    pub(crate) error_located: bool,
+    /// Byte order of `ia-*` identifiers, from `authoring-byte-order`.
+    pub(crate) byte_order: ByteOrder,
+    /// Option definitions used to type `option` statements.
+    pub(crate) options: OptionRegistry,
+    /// Server parameters of the global scope seen so far.
+    pub(crate) parameters: ServerParameters,
+    /// Dialect the input is checked against.
+    pub(crate) dialect: DialectState,
+    /// Strictness and limits requested by the caller.
+    pub(crate) settings: ParserOptions,
+    /// Number of statements enclosing the one being parsed.
+    pub(crate) depth: usize,
+    /// Number of statements other than `include` enclosing the one being
+    /// parsed. Statements at zero are in the global scope.
+    pub(crate) enclosing: usize,
+    /// Number of statements parsed so far.
+    pub(crate) declarations: usize,
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(settings: &ParserOptions, loader: Option<&'a dyn FileLoader>) -> Context<'a> {
+        Context {
+            loader,
+            dialect: DialectState::new(settings.dialect, settings.strict_dialect),
+            settings: settings.clone(),
+            ..Context::default()
+        }
+    }
+
+    /// Go one nesting level deeper, checking [`ParserOptions::max_depth`].
+    pub(crate) fn enter(&mut self) -> Result<(), String> {
+        if let Some(max) = self.settings.max_depth {
+            if self.depth >= max {
+                return Err(format!("Nesting deeper than {} levels", max));
+            }
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+/// Default for [`ParserOptions::max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// How strictly to parse, and limits on the resources spent parsing, e.g.
+/// for untrusted input:
+///
+/// ```
+/// use dhcpd_parser::parser::ParserOptions;
+///
+/// let options = ParserOptions::new()
+///     .strict(true)
+///     .max_depth(16)
+///     .max_token_length(1024)
+///     .max_declarations(10_000);
+/// ```
+///
+/// The defaults are lenient about unknown statements and mistyped option
+/// values, strict inside `lease` blocks, auto-detect the dialect and limit the nesting depth to
+/// [`DEFAULT_MAX_DEPTH`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserOptions {
+    pub(crate) dialect: Dialect,
+    pub(crate) strict_statements: bool,
+    pub(crate) strict_leases: bool,
+    pub(crate) strict_options: bool,
+    pub(crate) strict_dialect: bool,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_token_length: Option<usize>,
+    pub(crate) max_declarations: Option<usize>,
 }
 
-/// Tolerantly parse a sequence of declarations. Recognized: `lease` and `host`.
-/// Anything else (subnet, group, shared-network, option, single statements…) is
-/// skipped; `{...}` blocks are recursed into so nested `host` declarations are
-/// still collected. With `in_braces`, returns when the matching `}` is consumed.
-fn parse_declarations<'l, T>(
-    it: &mut Peekable<T>,
-    leases: &mut Leases,
-    hosts: &mut Vec<Host>,
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            dialect: Dialect::Auto,
+            strict_statements: false,
+            strict_leases: true,
+            strict_options: false,
+            strict_dialect: false,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_token_length: None,
+            max_declarations: None,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> ParserOptions {
+        ParserOptions::default()
+    }
+
+    /// Grammar to accept. With [`Dialect::Auto`], the first construct
+    /// specific to one dialect selects it.
+    pub fn dialect(mut self, dialect: Dialect) -> ParserOptions {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Set all of [`strict_statements`](ParserOptions::strict_statements),
+    /// [`strict_leases`](ParserOptions::strict_leases),
+    /// [`strict_options`](ParserOptions::strict_options) and
+    /// [`strict_dialect`](ParserOptions::strict_dialect).
+    pub fn strict(self, strict: bool) -> ParserOptions {
+        self.strict_statements(strict)
+            .strict_leases(strict)
+            .strict_options(strict)
+            .strict_dialect(strict)
+    }
+
+    /// Reject blocks of unknown declarations and stray braces instead of
+    /// keeping them as [`Unknown`] nodes.
+    pub fn strict_statements(mut self, strict: bool) -> ParserOptions {
+        self.strict_statements = strict;
+        self
+    }
+
+    /// Reject unknown entries inside `lease` blocks instead of keeping them
+    /// in [`Lease::unknown`].
+    pub fn strict_leases(mut self, strict: bool) -> ParserOptions {
+        self.strict_leases = strict;
+        self
+    }
+
+    /// Reject option values that don't match the type of their definition
+    /// instead of keeping the tokens as written in [`OptionValue::Unknown`].
+    ///
+    /// [`OptionValue::Unknown`]: crate::options::OptionValue::Unknown
+    pub fn strict_options(mut self, strict: bool) -> ParserOptions {
+        self.strict_options = strict;
+        self
+    }
+
+    /// Reject constructs the dialect in effect doesn't accept, instead of
+    /// accepting those of both dialects.
+    pub fn strict_dialect(mut self, strict: bool) -> ParserOptions {
+        self.strict_dialect = strict;
+        self
+    }
+
+    /// Maximum number of nested statements, counting `include` statements as
+    /// enclosing the statements of the files they include.
+    pub fn max_depth(mut self, depth: usize) -> ParserOptions {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Maximum length of a single token in bytes, quotes included.
+    pub fn max_token_length(mut self, length: usize) -> ParserOptions {
+        self.max_token_length = Some(length);
+        self
+    }
+
+    /// Maximum number of statements and declarations, at any nesting level
+    /// and in all included files.
+    pub fn max_declarations(mut self, count: usize) -> ParserOptions {
+        self.max_declarations = Some(count);
+        self
+    }
+}
+
+/// Check the lexed tokens of an input against
+/// [`ParserOptions::max_token_length`]. Errors are located.
+pub(crate) fn check_tokens(tokens: &[Token], ctx: &mut Context) -> Result<(), String> {
+    let max = match ctx.settings.max_token_length {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    match tokens.iter().find(|t| t.span.end - t.span.start > max) {
+        Some(t) => {
+            ctx.error_located = true;
+            Err(format!("{}: Token longer than {} bytes", t.span, max))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Parse a statement not modeled otherwise: a generic parameter, recorded
+/// in the typed server parameters when `global`, or a block of unknown
+/// declarations.
+fn parse_other_statement(
+    it: &mut Cursor,
+    ctx: &mut Context,
+    global: bool,
+) -> Result<StatementKind, String> {
+    if is_block(it) {
+        if ctx.settings.strict_statements {
+            let keyword = it.next().map(|t| t.to_string()).unwrap_or_default();
+            return Err(format!("Unknown declaration {:?}", keyword));
+        }
+        let unknown = parse_unknown(it, ctx.depth, ctx.settings.max_depth)?;
+        return Ok(StatementKind::Unknown(unknown));
+    }
+
+    let parameter = parse_parameter(it)?;
+    if global {
+        ctx.parameters.apply(&parameter)?;
+    }
+
+    Ok(StatementKind::Parameter(parameter))
+}
+
+/// The dialect that accepts a statement, and how to describe the statement,
+/// when only one of them does. OpenBSD dhcpd has no classes, pools,
+/// conditionals, DHCPv6, failover or DNS updates.
+fn statement_dialect(kind: &StatementKind) -> Option<(Dialect, &'static str)> {
+    let what = match kind {
+        StatementKind::Subnet6(_) => "subnet6",
+        StatementKind::Range6(_) => "range6",
+        StatementKind::Prefix6(_) => "prefix6",
+        StatementKind::Pool(_) => "pool",
+        StatementKind::Class(_) => "class",
+        StatementKind::Subclass(_) => "subclass",
+        StatementKind::If(_) => "if",
+        StatementKind::On(_) => "on",
+        StatementKind::Set(_) => "set",
+        StatementKind::Unset(_) => "unset",
+        StatementKind::Include(_) => "include",
+        StatementKind::OptionSpace(_) => "option space",
+        StatementKind::OptionDefinition(_) => "option definition",
+        StatementKind::Ia(_) => "ia",
+        StatementKind::FailoverPeerState(_) | StatementKind::FailoverPeer(_) => "failover peer",
+        StatementKind::Key(_) => "key",
+        StatementKind::Zone(_) => "zone",
+        StatementKind::AuthoringByteOrder(_) => "authoring-byte-order",
+        StatementKind::ServerDuid(_) => "server-duid",
+        _ => return None,
+    };
+
+    Some((Dialect::Isc, what))
+}
+
+/// Parse the statement starting at the current token. Returns `None` for
+/// empty statements.
+pub(crate) fn parse_statement(
+    it: &mut Cursor,
+    ctx: &mut Context,
+) -> Result<Option<Statement>, String> {
+    ctx.enter()?;
+    let global = ctx.enclosing == 0;
+    let include = matches!(it.peek().copied(), Some(LexItem::Word(w)) if w.as_str() == "include");
+    if !include {
+        ctx.enclosing += 1;
+    }
+    let statement = parse_statement_at_depth(it, ctx, global)?;
+    if !include {
+        ctx.enclosing -= 1;
+    }
+    ctx.depth -= 1;
+
+    if statement.is_some() {
+        ctx.declarations += 1;
+        if let Some(max) = ctx.settings.max_declarations {
+            if ctx.declarations > max {
+                return Err(format!("More than {} declarations", max));
+            }
+        }
+    }
+
+    Ok(statement)
+}
+
+fn parse_statement_at_depth(
+    it: &mut Cursor,
+    ctx: &mut Context,
+    global: bool,
+) -> Result<Option<Statement>, String> {
+    let span = it.span();
+    let kind = match it.peek().copied() {
+        None => return Err("Statement expected".to_owned()),
+        Some(LexItem::Endl) => {
+            it.next();
+            return Ok(None);
+        }
+        Some(LexItem::Paren('{')) if ctx.settings.strict_statements => {
+            return Err("Unexpected '{'".to_owned());
+        }
+        Some(LexItem::Paren('{')) => {
+            StatementKind::Unknown(parse_unknown(it, ctx.depth, ctx.settings.max_depth)?)
+        }
+        Some(LexItem::Decl(ConfigKeyword::Lease)) => {
+            StatementKind::Lease(parse_lease_decl(it, ctx)?)
+        }
+        Some(LexItem::Opt(LeaseKeyword::Set)) => StatementKind::Set(parse_set(it)?),
+        Some(LexItem::Word(w)) => match w.as_str() {
+            "host" => StatementKind::Host(parse_host(it, ctx)?),
+            "shared-network" => StatementKind::SharedNetwork(parse_shared_network(it, ctx)?),
+            "subnet" => StatementKind::Subnet(parse_subnet(it, ctx)?),
+            "subnet6" => StatementKind::Subnet6(parse_subnet6(it, ctx)?),
+            "group" => StatementKind::Group(parse_group(it, ctx)?),
+            "pool" => StatementKind::Pool(parse_pool(it, ctx)?),
+            "class" => StatementKind::Class(parse_class(it, ctx)?),
+            "subclass" => StatementKind::Subclass(parse_subclass(it, ctx)?),
+            "range" => StatementKind::Range(parse_range(it)?),
+            "range6" => StatementKind::Range6(parse_range6(it)?),
+            "prefix6" => StatementKind::Prefix6(parse_prefix6(it)?),
+            "if" => StatementKind::If(parse_if(it, ctx)?),
+            "on" => StatementKind::On(parse_on(it, ctx)?),
+            "unset" => StatementKind::Unset(parse_unset(it)?),
+            "include" => StatementKind::Include(parse_include(it, ctx)?),
+            "option" if is_option_space(it) => {
+                StatementKind::OptionSpace(parse_option_space(it, &mut ctx.options)?)
+            }
+            "option" if is_option_definition(it) => {
+                StatementKind::OptionDefinition(parse_option_definition(it, &mut ctx.options)?)
+            }
+            "option" => {
+                StatementKind::Option(parse_option(it, &ctx.options, ctx.settings.strict_options)?)
+            }
+            "ia-na" | "ia-ta" | "ia-pd" => StatementKind::Ia(parse_ia(it, ctx)?),
+            "failover" if is_failover_peer_state(it) => {
+                StatementKind::FailoverPeerState(parse_failover_peer_state(it, ctx)?)
+            }
+            "key" => StatementKind::Key(parse_key(it)?),
+            "zone" => StatementKind::Zone(parse_zone(it)?),
+            "failover" => {
+                let peer = parse_failover_peer(it)?;
+                ctx.failover_peers.push(peer.name.clone());
+                StatementKind::FailoverPeer(peer)
+            }
+            "authoring-byte-order" => {
+                let byte_order = parse_authoring_byte_order(it)?;
+                ctx.byte_order = byte_order;
+                it.next(); // ";"
+                StatementKind::AuthoringByteOrder(byte_order)
+            }
+            "server-duid" => {
+                let duid = parse_server_duid(it)?;
+                it.next(); // ";"
+                StatementKind::ServerDuid(duid)
+            }
+            _ => parse_other_statement(it, ctx, global)?,
+        },
+        Some(_) => parse_other_statement(it, ctx, global)?,
+    };
+
+    if let Some((dialect, what)) = statement_dialect(&kind) {
+        ctx.dialect.construct(dialect, what).map_err(|err| {
+            ctx.error_located = true;
+            format!("{}: {}", span, err)
+        })?;
+    }
+
+    Ok(Some(Statement {
+        kind,
+        span: it.span_from(&span),
+    }))
+}
+
+/// Tolerantly parse a sequence of declarations into syntax tree nodes.
+/// Simple statements that aren't modeled otherwise become generic
+/// parameters, and blocks of unknown declarations [`Unknown`] nodes. With
+/// `in_braces`, returns when the matching `}` is consumed.
+pub(crate) fn parse_declarations(
+    it: &mut Cursor,
+    ctx: &mut Context,
     in_braces: bool,
-) -> Result<(), String>
-where
-    T: Iterator<Item = &'l LexItem>,
-{
+) -> Result<Vec<Statement>, String> {
+    let mut statements = Vec::new();
     loop {
         match it.peek().copied() {
             None => {
                 if in_braces {
                     return Err("Unexpected EOF: unclosed '{'".to_owned());
                 }
-                return Ok(());
+                return Ok(statements);
             }
             Some(LexItem::Paren('}')) => {
                 it.next();
                 if in_braces {
-                    return Ok(());
+                    return Ok(statements);
+                }
+                if ctx.settings.strict_statements {
+                    return Err("Unexpected '}'".to_owned());
                 }
                 // Stray closing brace at top level: ignore and continue.
             }
-            Some(LexItem::Decl(ConfigKeyword::Lease)) => parse_lease_decl(it, leases)?,
-            Some(LexItem::Word(w)) if w.as_str() == "host" => {
-                let host = parse_host(it)?;
-                hosts.push(host);
-            }
-            Some(LexItem::Endl) => {
-                it.next();
-            }
-            _ => {
-                // Unknown statement or block: skip its header up to ';' or '{'.
-                // On '{', recurse so nested `host`/`lease` declarations are kept.
-                loop {
-                    match it.peek().copied() {
-                        None => return Ok(()),
-                        Some(LexItem::Endl) => {
-                            it.next();
-                            break;
-                        }
-                        Some(LexItem::Paren('{')) => {
-                            it.next();
-                            parse_declarations(it, leases, hosts, true)?;
-                            break;
-                        }
-                        Some(LexItem::Paren('}')) => break,
-                        Some(_) => {
-                            it.next();
-                        }
-                    }
+            Some(_) => {
+                if let Some(statement) = parse_statement(it, ctx)? {
+                    statements.push(statement);
                 }
             }
         }
     }
 }
 
-fn parse_config(tokens: Vec<LexItem>) -> Result<ParserResult, String> {
-    let mut leases = Leases::new();
-    let mut hosts = Vec::new();
+/// Gather the entries of the leases file, and hosts from any nesting level.
+fn collect(statements: &[Statement], result: &mut ParserResult) {
+    for statement in statements {
+        match &statement.kind {
+            StatementKind::Lease(lease) => result.leases.push(lease.clone()),
+            StatementKind::Host(host) => result.hosts.push(host.clone()),
+            StatementKind::Ia(ia) => result.ias.push(ia.clone()),
+            StatementKind::AuthoringByteOrder(byte_order) => {
+                result.authoring_byte_order = Some(*byte_order)
+            }
+            StatementKind::ServerDuid(duid) => result.server_duid = Some(duid.clone()),
+            StatementKind::FailoverPeerState(peer) => result.failover_peers.push(peer.clone()),
+            StatementKind::Class(class) => result.classes.push(class.clone()),
+            StatementKind::Subclass(subclass) => result.subclasses.push(subclass.clone()),
+            _ => {}
+        }
+        collect(statement.children(), result);
+    }
+}
 
-    let mut it = tokens.iter().peekable();
-    parse_declarations(&mut it, &mut leases, &mut hosts, false)?;
+fn parse_tokens(tokens: &[Token], ctx: &mut Context) -> Result<Config, String> {
+    let mut it = Cursor::new(tokens);
 
-    Ok(ParserResult { leases, hosts })
+    match parse_declarations(&mut it, ctx, false) {
+        Ok(statements) => Ok(Config {
+            statements,
+            parameters: ctx.parameters.clone(),
+            dialect: ctx.dialect.resolved(),
+        }),
+        Err(err) if ctx.error_located => Err(err),
+        Err(err) => {
+            let span = it.prev_span().cloned().unwrap_or_else(|| it.span());
+            Err(format!("{}: {}", span, err))
+        }
+    }
 }
 
-pub fn parse<S>(input: S) -> Result<ParserResult, String>
+/// Parse a configuration file, such as `dhcpd.conf`, into a syntax tree.
+/// `include` statements are recorded but not followed.
+pub fn parse_config<S>(input: S) -> Result<Config, String>
+where
+    S: Into<String>,
+{
+    parse_config_with(input, &ParserOptions::default())
+}
+
+/// Like [`parse_config`], with the strictness and limits of `options`.
+pub fn parse_config_with<S>(input: S, options: &ParserOptions) -> Result<Config, String>
 where
     S: Into<String>,
 {
     let tokens = lex(input).map_err(|err| format!("Lexer error: {err}"))?;
-    parse_config(tokens)
+    parse_lexed(&tokens, options)
+}
+
+/// Parse the tokens of a whole configuration file without following
+/// `include` statements.
+pub(crate) fn parse_lexed(tokens: &[Token], options: &ParserOptions) -> Result<Config, String> {
+    let mut ctx = Context::new(options, None);
+    check_tokens(tokens, &mut ctx)?;
+    parse_tokens(tokens, &mut ctx)
+}
+
+/// Parse the configuration file at `path` into a syntax tree, following
+/// `include` statements through `loader`. Spans and errors name the file
+/// they come from.
+pub fn parse_config_file(path: &str, loader: &dyn FileLoader) -> Result<Config, String> {
+    parse_config_file_with(path, loader, &ParserOptions::default())
+}
+
+/// Like [`parse_config_file`], with the strictness and limits of `options`.
+pub fn parse_config_file_with(
+    path: &str,
+    loader: &dyn FileLoader,
+    options: &ParserOptions,
+) -> Result<Config, String> {
+    let mut ctx = Context::new(options, Some(loader));
+    let statements = parse_included(path, &mut ctx)?;
+
+    Ok(Config {
+        statements,
+        parameters: ctx.parameters,
+        dialect: ctx.dialect.resolved(),
+    })
+}
+
+pub fn parse<S>(input: S) -> Result<ParserResult, String>
+where
+    S: Into<String>,
+{
+    Ok(result_from_config(&parse_config(input)?))
+}
+
+/// Like [`parse`], with the strictness and limits of `options`.
+pub fn parse_with<S>(input: S, options: &ParserOptions) -> Result<ParserResult, String>
+where
+    S: Into<String>,
+{
+    Ok(result_from_config(&parse_config_with(input, options)?))
+}
+
+/// Like [`parse`], reading the file at `path` and the files it includes
+/// through `loader`.
+pub fn parse_file(path: &str, loader: &dyn FileLoader) -> Result<ParserResult, String> {
+    Ok(result_from_config(&parse_config_file(path, loader)?))
+}
+
+/// Like [`parse_file`], with the strictness and limits of `options`.
+pub fn parse_file_with(
+    path: &str,
+    loader: &dyn FileLoader,
+    options: &ParserOptions,
+) -> Result<ParserResult, String> {
+    Ok(result_from_config(&parse_config_file_with(
+        path, loader, options,
+    )?))
+}
+
+fn result_from_config(config: &Config) -> ParserResult {
+    let mut result = ParserResult {
+        leases: Leases::new(),
+        hosts: Vec::new(),
+        ias: Vec::new(),
+        authoring_byte_order: None,
+        server_duid: None,
+        failover_peers: Vec::new(),
+        classes: Vec::new(),
+        subclasses: Vec::new(),
+        dialect: config.dialect,
+    };
+    collect(&config.statements, &mut result);
+
+    result
 }
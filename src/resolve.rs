@@ -0,0 +1,354 @@
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+
+use crate::common::quote;
+use crate::common::unquote;
+use crate::config::walk_includes;
+use crate::config::Config;
+use crate::config::Parameter;
+use crate::config::ScopeEntry;
+use crate::config::ScopePath;
+use crate::config::Statement;
+use crate::config::StatementKind;
+use crate::options::OptionStatement;
+use crate::parser::Host;
+
+/// A setting together with the scope it was taken from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sourced<T> {
+    pub value: T,
+    pub scope: ScopePath,
+}
+
+/// Options and parameters in effect for a client, after applying the
+/// enclosing scopes from the global one inwards.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EffectiveConfig {
+    pub options: Vec<Sourced<OptionStatement>>,
+    pub parameters: Vec<Sourced<Parameter>>,
+}
+
+impl EffectiveConfig {
+    /// Effective option, looked up by its name as written, e.g. `routers` or
+    /// `dhcp6.name-servers`.
+    pub fn option(&self, name: &str) -> Option<&Sourced<OptionStatement>> {
+        self.options.iter().find(|o| o.value.full_name() == name)
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&Sourced<Parameter>> {
+        self.parameters.iter().find(|p| p.value.name == name)
+    }
+
+    fn parameter_u32(&self, name: &str) -> Option<u32> {
+        self.parameter(name)?
+            .value
+            .args
+            .first()?
+            .parse::<u32>()
+            .ok()
+    }
+
+    fn parameter_str(&self, name: &str) -> Option<String> {
+        let arg = self.parameter(name)?.value.args.first()?;
+        Some(unquote(arg))
+    }
+
+    pub fn default_lease_time(&self) -> Option<u32> {
+        self.parameter_u32("default-lease-time")
+    }
+
+    pub fn max_lease_time(&self) -> Option<u32> {
+        self.parameter_u32("max-lease-time")
+    }
+
+    pub fn filename(&self) -> Option<String> {
+        self.parameter_str("filename")
+    }
+
+    pub fn next_server(&self) -> Option<String> {
+        self.parameter_str("next-server")
+    }
+
+    /// Apply the statements of one scope, overriding settings of outer ones.
+    fn apply(&mut self, statements: &[Statement], scope: &ScopePath) {
+        walk_includes(statements, &mut |s| match &s.kind {
+            StatementKind::Option(v) => self.set_option(v.clone(), scope),
+            StatementKind::Parameter(v) => self.set_parameter(v.clone(), scope),
+            _ => {}
+        });
+    }
+
+    fn set_option(&mut self, value: OptionStatement, scope: &ScopePath) {
+        let sourced = Sourced {
+            value,
+            scope: scope.clone(),
+        };
+        let name = sourced.value.full_name();
+        match self
+            .options
+            .iter_mut()
+            .find(|o| o.value.full_name() == name)
+        {
+            Some(existing) => *existing = sourced,
+            None => self.options.push(sourced),
+        }
+    }
+
+    fn set_parameter(&mut self, value: Parameter, scope: &ScopePath) {
+        let sourced = Sourced {
+            value,
+            scope: scope.clone(),
+        };
+        match self
+            .parameters
+            .iter_mut()
+            .find(|p| p.value.name == sourced.value.name)
+        {
+            Some(existing) => *existing = sourced,
+            None => self.parameters.push(sourced),
+        }
+    }
+
+    /// Apply a host scope: its statements and the parameters modeled as
+    /// fields of [`Host`].
+    fn apply_host(&mut self, host: &Host, scope: &ScopePath) {
+        self.apply(&host.statements, scope);
+        let fields = [
+            ("filename", host.filename.as_ref(), true),
+            ("next-server", host.next_server.as_ref(), false),
+            ("ddns-hostname", host.ddns_hostname.as_ref(), true),
+        ];
+        for (name, value, quoted) in fields.iter() {
+            if let Some(v) = value {
+                let arg = if *quoted { quote(v) } else { v.to_string() };
+                let parameter = Parameter {
+                    name: name.to_string(),
+                    args: vec![arg],
+                };
+                self.set_parameter(parameter, scope);
+            }
+        }
+    }
+}
+
+/// A scope declaration enclosing the statement being visited, with its
+/// statements.
+type Frame<'a> = (ScopeEntry, &'a [Statement]);
+
+pub(crate) fn scope_of<'a>(statement: &'a Statement) -> Option<Frame<'a>> {
+    let entry = match &statement.kind {
+        StatementKind::SharedNetwork(v) => ScopeEntry::SharedNetwork(v.name.clone()),
+        StatementKind::Subnet(v) => ScopeEntry::Subnet {
+            network: v.network,
+            prefix_len: v.prefix_len,
+        },
+        StatementKind::Subnet6(v) => ScopeEntry::Subnet6 {
+            network: v.network,
+            prefix_len: v.prefix_len,
+        },
+        StatementKind::Group(v) => ScopeEntry::Group(v.name.clone()),
+        StatementKind::Pool(_) => ScopeEntry::Pool,
+        _ => return None,
+    };
+    Some((entry, statement.children()))
+}
+
+/// Visit statements depth first, passing the enclosing scopes, until `found`
+/// returns true. Included files are visited in place.
+fn search<'a, F>(statements: &'a [Statement], frames: &mut Vec<Frame<'a>>, found: &mut F) -> bool
+where
+    F: FnMut(&'a Statement, &[Frame<'a>]) -> bool,
+{
+    for statement in statements {
+        if let StatementKind::Include(_) = &statement.kind {
+            if search(statement.children(), frames, found) {
+                return true;
+            }
+            continue;
+        }
+        if found(statement, frames) {
+            return true;
+        }
+        if let Some(frame) = scope_of(statement) {
+            let children = frame.1;
+            frames.push(frame);
+            let done = search(children, frames, found);
+            frames.pop();
+            if done {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Apply the global scope and `frames`, outermost first.
+fn resolve_frames(config: &Config, frames: &[Frame]) -> (EffectiveConfig, ScopePath) {
+    let mut effective = EffectiveConfig::default();
+    let mut scope = ScopePath::default();
+    effective.apply(&config.statements, &scope);
+    for (entry, statements) in frames {
+        scope.entries.push(entry.clone());
+        effective.apply(statements, &scope);
+    }
+    (effective, scope)
+}
+
+/// Find the first statement matching `pred` together with the scopes around
+/// it, including its own if it is a scope declaration.
+fn find_frames<'a, P>(config: &'a Config, mut pred: P) -> Option<(&'a Statement, Vec<Frame<'a>>)>
+where
+    P: FnMut(&Statement) -> bool,
+{
+    let mut result = None;
+    search(&config.statements, &mut Vec::new(), &mut |s, frames| {
+        if !pred(s) {
+            return false;
+        }
+        let mut frames = frames.to_vec();
+        frames.extend(scope_of(s));
+        result = Some((s, frames));
+        true
+    });
+    result
+}
+
+/// Scopes containing the first fixed address of `host` that lies in a range
+/// or subnet, like dhcpd applies to hosts declared outside of a subnet.
+fn address_frames<'a>(config: &'a Config, host: &Host) -> Vec<Frame<'a>> {
+    for ip in host
+        .fixed_addresses
+        .iter()
+        .filter_map(|a| a.parse::<Ipv4Addr>().ok())
+    {
+        let found = find_frames(config, |s| match &s.kind {
+            StatementKind::Range(r) => r.contains(ip),
+            _ => false,
+        })
+        .or_else(|| {
+            find_frames(config, |s| match &s.kind {
+                StatementKind::Subnet(v) => v.contains(ip),
+                _ => false,
+            })
+        });
+        if let Some((_, frames)) = found {
+            return frames;
+        }
+    }
+    for &ip in host.fixed_addresses6.iter() {
+        let found = find_frames(config, |s| match &s.kind {
+            StatementKind::Range6(r) => r.contains(ip),
+            _ => false,
+        })
+        .or_else(|| {
+            find_frames(config, |s| match &s.kind {
+                StatementKind::Subnet6(v) => v.contains(ip),
+                _ => false,
+            })
+        });
+        if let Some((_, frames)) = found {
+            return frames;
+        }
+    }
+    Vec::new()
+}
+
+/// Find the first statement matching `pred` and resolve the scopes around it,
+/// including its own if it is a scope declaration. A host declared outside of
+/// a subnet gets the scopes containing its fixed address applied before its
+/// own enclosing ones.
+fn resolve_statement<P>(config: &Config, pred: P) -> Option<EffectiveConfig>
+where
+    P: FnMut(&Statement) -> bool,
+{
+    let (s, frames) = find_frames(config, pred)?;
+    let host = match &s.kind {
+        StatementKind::Host(host) => host,
+        _ => return Some(resolve_frames(config, &frames).0),
+    };
+
+    let in_subnet = frames.iter().any(|(entry, _)| {
+        matches!(
+            entry,
+            ScopeEntry::Subnet { .. } | ScopeEntry::Subnet6 { .. }
+        )
+    });
+    let outer = if in_subnet {
+        Vec::new()
+    } else {
+        address_frames(config, host)
+    };
+    let (mut effective, _) = resolve_frames(config, &outer);
+
+    let mut scope = ScopePath::default();
+    for (entry, statements) in frames.iter() {
+        scope.entries.push(entry.clone());
+        effective.apply(statements, &scope);
+    }
+    scope.entries.push(ScopeEntry::Host(host.name.clone()));
+    effective.apply_host(host, &scope);
+    Some(effective)
+}
+
+/// Effective configuration of a host declared in `config`, or `None` if the
+/// host is not part of it.
+pub fn resolve_host(config: &Config, host: &Host) -> Option<EffectiveConfig> {
+    resolve_statement(
+        config,
+        |s| matches!(&s.kind, StatementKind::Host(h) if h == host),
+    )
+}
+
+/// Effective configuration of the host with hardware address `mac`.
+pub fn resolve_mac(config: &Config, mac: &str) -> Option<EffectiveConfig> {
+    let mac = mac.to_lowercase();
+    resolve_statement(config, |s| match &s.kind {
+        StatementKind::Host(h) => h.mac.as_deref() == Some(mac.as_str()),
+        _ => false,
+    })
+}
+
+/// Effective configuration of a client given `ip`: the host with that fixed
+/// address if there is one, otherwise the scopes of the range or subnet
+/// containing it.
+pub fn resolve_address(config: &Config, ip: Ipv4Addr) -> Option<EffectiveConfig> {
+    let ip_str = ip.to_string();
+    resolve_statement(config, |s| match &s.kind {
+        StatementKind::Host(h) => h.fixed_addresses.contains(&ip_str),
+        _ => false,
+    })
+    .or_else(|| {
+        resolve_statement(config, |s| match &s.kind {
+            StatementKind::Range(r) => r.contains(ip),
+            _ => false,
+        })
+    })
+    .or_else(|| {
+        resolve_statement(config, |s| match &s.kind {
+            StatementKind::Subnet(v) => v.contains(ip),
+            _ => false,
+        })
+    })
+}
+
+/// Like [`resolve_address`] for a DHCPv6 client: the host with fixed address
+/// `ip` if there is one, otherwise the scopes of the range6 or subnet6
+/// containing it.
+pub fn resolve_address6(config: &Config, ip: Ipv6Addr) -> Option<EffectiveConfig> {
+    resolve_statement(config, |s| match &s.kind {
+        StatementKind::Host(h) => h.fixed_addresses6.contains(&ip),
+        _ => false,
+    })
+    .or_else(|| {
+        resolve_statement(config, |s| match &s.kind {
+            StatementKind::Range6(r) => r.contains(ip),
+            _ => false,
+        })
+    })
+    .or_else(|| {
+        resolve_statement(config, |s| match &s.kind {
+            StatementKind::Subnet6(v) => v.contains(ip),
+            _ => false,
+        })
+    })
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::common::unquote;
+use crate::config::Parameter;
+
+/// Value of `ddns-update-style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdnsUpdateStyle {
+    AdHoc,
+    Interim,
+    Standard,
+    None,
+}
+
+impl fmt::Display for DdnsUpdateStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let r = match self {
+            DdnsUpdateStyle::AdHoc => "ad-hoc",
+            DdnsUpdateStyle::Interim => "interim",
+            DdnsUpdateStyle::Standard => "standard",
+            DdnsUpdateStyle::None => "none",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl DdnsUpdateStyle {
+    pub fn from(s: &str) -> Result<DdnsUpdateStyle, String> {
+        match s {
+            "ad-hoc" => Ok(DdnsUpdateStyle::AdHoc),
+            "interim" => Ok(DdnsUpdateStyle::Interim),
+            "standard" => Ok(DdnsUpdateStyle::Standard),
+            "none" => Ok(DdnsUpdateStyle::None),
+            _ => Err(format!("'{}' is not a recognized ddns-update-style", s)),
+        }
+    }
+}
+
+/// Common server parameters set in the global scope. Fields are `None` when
+/// the parameter is not set, so that dhcpd's defaults apply.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ServerParameters {
+    /// `authoritative;` or `not authoritative;`
+    pub authoritative: Option<bool>,
+    pub default_lease_time: Option<u32>,
+    pub max_lease_time: Option<u32>,
+    pub min_lease_time: Option<u32>,
+    pub ddns_update_style: Option<DdnsUpdateStyle>,
+    pub log_facility: Option<String>,
+    pub ping_check: Option<bool>,
+    pub one_lease_per_client: Option<bool>,
+    pub use_host_decl_names: Option<bool>,
+    pub server_identifier: Option<String>,
+    pub next_server: Option<String>,
+    pub filename: Option<String>,
+}
+
+fn single_arg(parameter: &Parameter) -> Result<&str, String> {
+    match parameter.args.as_slice() {
+        [arg] => Ok(arg),
+        _ => Err(format!(
+            "{} expects one argument, got {}",
+            parameter.name,
+            parameter.args.len()
+        )),
+    }
+}
+
+fn parse_u32(parameter: &Parameter) -> Result<u32, String> {
+    let arg = single_arg(parameter)?;
+    arg.parse::<u32>()
+        .map_err(|_| format!("Invalid {} {:?}", parameter.name, arg))
+}
+
+/// Flags are set by `on`/`true` and cleared by `off`/`false`; a flag written
+/// without an argument is set.
+fn parse_flag(parameter: &Parameter) -> Result<bool, String> {
+    if parameter.args.is_empty() {
+        return Ok(true);
+    }
+    match single_arg(parameter)? {
+        "on" | "true" => Ok(true),
+        "off" | "false" => Ok(false),
+        arg => Err(format!("Invalid {} {:?}", parameter.name, arg)),
+    }
+}
+
+impl ServerParameters {
+    /// Record a parameter of the global scope. Parameters not modeled here
+    /// are ignored, a later statement overrides an earlier one.
+    pub(crate) fn apply(&mut self, parameter: &Parameter) -> Result<(), String> {
+        match parameter.name.as_str() {
+            "authoritative" if parameter.args.is_empty() => self.authoritative = Some(true),
+            "not" if parameter.args == ["authoritative"] => self.authoritative = Some(false),
+            "default-lease-time" => self.default_lease_time = Some(parse_u32(parameter)?),
+            "max-lease-time" => self.max_lease_time = Some(parse_u32(parameter)?),
+            "min-lease-time" => self.min_lease_time = Some(parse_u32(parameter)?),
+            "ddns-update-style" => {
+                self.ddns_update_style = Some(DdnsUpdateStyle::from(single_arg(parameter)?)?)
+            }
+            "log-facility" => self.log_facility = Some(single_arg(parameter)?.to_owned()),
+            "ping-check" => self.ping_check = Some(parse_flag(parameter)?),
+            "one-lease-per-client" => self.one_lease_per_client = Some(parse_flag(parameter)?),
+            "use-host-decl-names" => self.use_host_decl_names = Some(parse_flag(parameter)?),
+            "server-identifier" => self.server_identifier = Some(single_arg(parameter)?.to_owned()),
+            "next-server" => self.next_server = Some(single_arg(parameter)?.to_owned()),
+            "filename" => self.filename = Some(unquote(single_arg(parameter)?)),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,502 @@
+use std::net::IpAddr;
+
+use crate::classes::ClassDecl;
+use crate::classes::ClassMatch;
+use crate::classes::SubclassDecl;
+use crate::common::decode_hex;
+use crate::common::encode_escaped;
+use crate::common::quote;
+use crate::common::Date;
+use crate::config::Statement;
+use crate::config::StatementKind;
+use crate::config::Unknown;
+use crate::dialect::Dialect;
+use crate::failover::FailoverPeerState;
+use crate::failover::FailoverStateEntry;
+use crate::leases::BindingState;
+use crate::leases::Lease;
+use crate::leases::LeasesMethods;
+use crate::leases6::ByteOrder;
+use crate::leases6::Ia;
+use crate::parser::Host;
+use crate::parser::ParserResult;
+
+/// Output of the writer, one entry per line, indented by nesting level.
+struct Writer {
+    dialect: Dialect,
+    out: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn new(dialect: Dialect) -> Writer {
+        Writer {
+            dialect,
+            out: String::new(),
+            depth: 0,
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        // OpenBSD dhcpd indents with tabs, ISC dhcpd with two spaces.
+        let indent = match self.dialect {
+            Dialect::OpenBsd => "\t",
+            _ => "  ",
+        };
+        for _ in 0..self.depth {
+            self.out.push_str(indent);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Start a block with `head`, an empty head for a bare `{`.
+    fn open(&mut self, head: &str) {
+        if head.is_empty() {
+            self.line("{");
+        } else {
+            self.line(&format!("{} {{", head));
+        }
+        self.depth += 1;
+    }
+
+    /// Close a block and start the next one of the same statement, e.g. an
+    /// `elsif` branch.
+    fn reopen(&mut self, head: &str) {
+        self.depth -= 1;
+        self.line(&format!("}} {} {{", head));
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn date(&mut self, name: &str, date: &Option<Date>) {
+        if let Some(date) = date {
+            self.line(&format!("{} {};", name, write_date(date, self.dialect)));
+        }
+    }
+}
+
+/// Write a date the way dhcpd does in its leases file, e.g.
+/// `1 1985/01/01 00:00:00 UTC`. ISC dhcpd leaves out the time zone.
+pub fn write_date(date: &Date, dialect: Dialect) -> String {
+    let result = format!(
+        "{} {}/{:0>2}/{:0>2} {:0>2}:{:0>2}:{:0>2}",
+        date.weekday, date.year, date.month, date.day, date.hour, date.minute, date.second,
+    );
+    match dialect {
+        Dialect::Isc => result,
+        _ => result + " UTC",
+    }
+}
+
+/// Bytes as a quoted string when they are all printable, and as
+/// colon-separated hex otherwise.
+fn write_bytes(bytes: &[u8]) -> String {
+    if bytes.iter().all(|b| (b' '..=b'~').contains(b)) {
+        format!("\"{}\"", encode_escaped(bytes))
+    } else {
+        bytes
+            .iter()
+            .map(|b| format!("{:x}", b))
+            .collect::<Vec<String>>()
+            .join(":")
+    }
+}
+
+/// A client identifier of a lease, which is kept as written: colon-separated
+/// hex stays bare, anything else is quoted.
+fn write_uid(uid: &str) -> String {
+    if decode_hex(uid).is_ok() {
+        uid.to_owned()
+    } else {
+        quote(uid)
+    }
+}
+
+fn unknown(w: &mut Writer, unknown: &Unknown) {
+    let mut head = vec![unknown.keyword.as_str()];
+    head.extend(unknown.tokens.iter().map(|t| t.as_str()));
+    let head = head.join(" ");
+    let head = head.trim_start();
+
+    match &unknown.children {
+        None => w.line(&format!("{};", head)),
+        Some(children) => {
+            w.open(head);
+            for child in children {
+                self::unknown(w, child);
+            }
+            w.close();
+        }
+    }
+}
+
+fn lease(w: &mut Writer, lease: &Lease) {
+    let dialect = w.dialect;
+
+    w.open(&format!("lease {}", lease.ip));
+    w.date("starts", &lease.dates.starts);
+    w.date("ends", &lease.dates.ends);
+    if dialect != Dialect::OpenBsd {
+        w.date("tstp", &lease.dates.tstp);
+        w.date("tsfp", &lease.dates.tsfp);
+        w.date("atsfp", &lease.dates.atsfp);
+        w.date("cltt", &lease.dates.cltt);
+        // ISC dhcpd always records the binding state, auto-detection only
+        // needs it when it differs from the default.
+        if dialect == Dialect::Isc || lease.binding_state != BindingState::Free {
+            w.line(&format!("binding state {};", lease.binding_state));
+        }
+        if let Some(state) = &lease.next_binding_state {
+            w.line(&format!("next binding state {};", state));
+        }
+        if let Some(state) = &lease.rewind_binding_state {
+            w.line(&format!("rewind binding state {};", state));
+        }
+    }
+    if let Some(hardware) = &lease.hardware {
+        w.line(&format!("hardware {} {};", hardware.h_type, hardware.mac));
+    }
+    if let Some(uid) = &lease.uid {
+        w.line(&format!("uid {};", write_uid(uid)));
+    }
+    if dialect == Dialect::OpenBsd && lease.binding_state == BindingState::Abandoned {
+        w.line("abandoned;");
+    }
+    if dialect != Dialect::OpenBsd {
+        if let Some(v) = &lease.vendor_class_identifier {
+            w.line(&format!("set vendor-class-identifier = {};", quote(v)));
+        }
+        for (name, value) in &lease.variables {
+            w.line(&format!("set {} = {};", name, value));
+        }
+    }
+    if let Some(v) = &lease.client_hostname {
+        w.line(&format!("client-hostname {};", quote(v)));
+    }
+    if dialect != Dialect::Isc {
+        if let Some(v) = &lease.hostname {
+            w.line(&format!("hostname {};", quote(v)));
+        }
+    }
+    for entry in &lease.unknown {
+        unknown(w, entry);
+    }
+    w.close();
+}
+
+fn statement(w: &mut Writer, statement: &Statement) -> Result<(), String> {
+    match &statement.kind {
+        StatementKind::Option(v) => w.line(&v.to_string()),
+        StatementKind::Parameter(v) if v.args.is_empty() => w.line(&format!("{};", v.name)),
+        StatementKind::Parameter(v) => w.line(&format!("{} {};", v.name, v.args.join(" "))),
+        StatementKind::Set(v) => w.line(&format!("set {} = {};", v.name, v.value)),
+        StatementKind::Unset(v) => w.line(&format!("unset {};", v)),
+        StatementKind::Unknown(v) => unknown(w, v),
+        StatementKind::If(v) => {
+            for (i, branch) in v.branches.iter().enumerate() {
+                if i == 0 {
+                    w.open(&format!("if {}", branch.condition));
+                } else {
+                    w.reopen(&format!("elsif {}", branch.condition));
+                }
+                statements(w, &branch.statements)?;
+            }
+            if let Some(else_statements) = &v.else_statements {
+                w.reopen("else");
+                statements(w, else_statements)?;
+            }
+            w.close();
+        }
+        StatementKind::On(v) => {
+            let events = v
+                .events
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>();
+            w.open(&format!("on {}", events.join(" or ")));
+            statements(w, &v.statements)?;
+            w.close();
+        }
+        _ => {
+            return Err(format!(
+                "{}: Statement cannot be written to a leases file",
+                statement.span
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn statements(w: &mut Writer, statements: &[Statement]) -> Result<(), String> {
+    for s in statements {
+        statement(w, s)?;
+    }
+
+    Ok(())
+}
+
+fn host(w: &mut Writer, host: &Host) -> Result<(), String> {
+    w.open(&format!("host {}", quote(&host.name)));
+    if host.dynamic {
+        w.line("dynamic;");
+    }
+    if host.deleted {
+        w.line("deleted;");
+    }
+    if let Some(mac) = &host.mac {
+        let h_type = host.hardware_type.as_deref().unwrap_or("ethernet");
+        w.line(&format!("hardware {} {};", h_type, mac));
+    }
+    if let Some(uid) = &host.uid {
+        w.line(&format!("uid {};", write_bytes(uid)));
+    }
+    if let Some(identifier) = &host.host_identifier {
+        match identifier.v6relopt {
+            None => w.line(&format!("host-identifier {}", identifier.option)),
+            Some(hop) => {
+                let option = identifier.option.to_string();
+                let option = option.strip_prefix("option ").unwrap_or(&option);
+                w.line(&format!("host-identifier v6relopt {} {}", hop, option));
+            }
+        }
+    }
+    if !host.fixed_addresses.is_empty() {
+        w.line(&format!(
+            "fixed-address {};",
+            host.fixed_addresses.join(", ")
+        ));
+    }
+    if !host.fixed_addresses6.is_empty() {
+        let addresses = host
+            .fixed_addresses6
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<String>>();
+        w.line(&format!("fixed-address6 {};", addresses.join(", ")));
+    }
+    if let Some((prefix, prefix_len)) = &host.fixed_prefix6 {
+        w.line(&format!("fixed-prefix6 {}/{};", prefix, prefix_len));
+    }
+    if let Some(v) = &host.ddns_hostname {
+        w.line(&format!("ddns-hostname {};", quote(v)));
+    }
+    if let Some(v) = &host.filename {
+        w.line(&format!("filename {};", quote(v)));
+    }
+    if let Some(v) = &host.next_server {
+        if v.parse::<IpAddr>().is_ok() {
+            w.line(&format!("next-server {};", v));
+        } else {
+            w.line(&format!("next-server {};", quote(v)));
+        }
+    }
+    statements(w, &host.statements)?;
+    w.close();
+
+    Ok(())
+}
+
+fn ia(w: &mut Writer, ia: &Ia, byte_order: ByteOrder) {
+    let mut id = match byte_order {
+        ByteOrder::LittleEndian => ia.iaid.to_le_bytes(),
+        ByteOrder::BigEndian => ia.iaid.to_be_bytes(),
+    }
+    .to_vec();
+    id.extend(ia.duid.to_bytes());
+
+    w.open(&format!("{} \"{}\"", ia.kind, encode_escaped(&id)));
+    w.date("cltt", &ia.cltt);
+    for address in &ia.addresses {
+        w.open(&format!("iaaddr {}", address.address));
+        w.line(&format!("binding state {};", address.binding_state));
+        if let Some(v) = address.preferred_life {
+            w.line(&format!("preferred-life {};", v));
+        }
+        if let Some(v) = address.max_life {
+            w.line(&format!("max-life {};", v));
+        }
+        w.date("ends", &address.ends);
+        for entry in &address.unknown {
+            unknown(w, entry);
+        }
+        w.close();
+    }
+    for prefix in &ia.prefixes {
+        w.open(&format!("iaprefix {}/{}", prefix.prefix, prefix.prefix_len));
+        w.line(&format!("binding state {};", prefix.binding_state));
+        if let Some(v) = prefix.preferred_life {
+            w.line(&format!("preferred-life {};", v));
+        }
+        if let Some(v) = prefix.max_life {
+            w.line(&format!("max-life {};", v));
+        }
+        w.date("ends", &prefix.ends);
+        for entry in &prefix.unknown {
+            unknown(w, entry);
+        }
+        w.close();
+    }
+    for entry in &ia.unknown {
+        unknown(w, entry);
+    }
+    w.close();
+}
+
+fn failover_state(w: &mut Writer, whose: &str, entry: &Option<FailoverStateEntry>) {
+    if let Some(entry) = entry {
+        match &entry.at {
+            Some(at) => w.line(&format!(
+                "{} state {} at {};",
+                whose,
+                entry.state,
+                write_date(at, w.dialect)
+            )),
+            None => w.line(&format!("{} state {};", whose, entry.state)),
+        }
+    }
+}
+
+fn failover_peer(w: &mut Writer, peer: &FailoverPeerState) {
+    w.open(&format!("failover peer {} state", quote(&peer.name)));
+    failover_state(w, "my", &peer.my_state);
+    failover_state(w, "partner", &peer.partner_state);
+    if let Some(mclt) = peer.mclt {
+        w.line(&format!("mclt {};", mclt));
+    }
+    for entry in &peer.unknown {
+        unknown(w, entry);
+    }
+    w.close();
+}
+
+/// Statements shared by `class` and `subclass` bodies.
+fn class_body(
+    w: &mut Writer,
+    dynamic: bool,
+    deleted: bool,
+    lease_limit: Option<u32>,
+    body: &[Statement],
+) -> Result<(), String> {
+    if dynamic {
+        w.line("dynamic;");
+    }
+    if deleted {
+        w.line("deleted;");
+    }
+    if let Some(limit) = lease_limit {
+        w.line(&format!("lease limit {};", limit));
+    }
+    statements(w, body)?;
+    w.close();
+
+    Ok(())
+}
+
+fn class(w: &mut Writer, class: &ClassDecl) -> Result<(), String> {
+    w.open(&format!("class {}", quote(&class.name)));
+    match &class.matches {
+        Some(ClassMatch::If(e)) => w.line(&format!("match if {};", e)),
+        Some(ClassMatch::Value(e)) => w.line(&format!("match {};", e)),
+        None => {}
+    }
+    if let Some(e) = &class.spawn_with {
+        w.line(&format!("spawn with {};", e));
+    }
+    class_body(
+        w,
+        class.dynamic,
+        class.deleted,
+        class.lease_limit,
+        &class.statements,
+    )
+}
+
+fn subclass(w: &mut Writer, subclass: &SubclassDecl) -> Result<(), String> {
+    w.open(&format!(
+        "subclass {} {}",
+        quote(&subclass.class),
+        write_bytes(&subclass.value)
+    ));
+    class_body(
+        w,
+        subclass.dynamic,
+        subclass.deleted,
+        subclass.lease_limit,
+        &subclass.statements,
+    )
+}
+
+/// Write a lease in the syntax of the leases file of `dialect`. Entries the
+/// dialect has no syntax for are left out; with [`Dialect::Auto`] every entry
+/// is written.
+pub fn write_lease(lease: &Lease, dialect: Dialect) -> String {
+    let mut w = Writer::new(dialect);
+    self::lease(&mut w, lease);
+    w.out
+}
+
+/// Write a host entry as ISC dhcpd records hosts created through OMAPI.
+/// Fails for statements that don't belong in a host entry, such as nested
+/// declarations.
+pub fn write_host(host: &Host) -> Result<String, String> {
+    let mut w = Writer::new(Dialect::Isc);
+    self::host(&mut w, host)?;
+    Ok(w.out)
+}
+
+/// Write the entries of a leases file, such that parsing the output gives the
+/// same entries again. With [`Dialect::Auto`] the dialect the result was
+/// parsed as is used; entries of ISC dhcpd are left out for OpenBSD.
+///
+/// Entries are grouped by kind rather than kept in their original order, and
+/// the [`Span`](crate::common::Span)s of statements and unknown entries
+/// parsed from the output refer to the output.
+pub fn write_result(result: &ParserResult, dialect: Dialect) -> Result<String, String> {
+    let dialect = match dialect {
+        Dialect::Auto => result.dialect.unwrap_or(Dialect::Auto),
+        d => d,
+    };
+    let mut w = Writer::new(dialect);
+    let isc = dialect != Dialect::OpenBsd;
+
+    if isc {
+        if let Some(byte_order) = result.authoring_byte_order {
+            w.line(&format!("authoring-byte-order {};", byte_order));
+        }
+        if let Some(duid) = &result.server_duid {
+            w.line(&format!(
+                "server-duid \"{}\";",
+                encode_escaped(&duid.to_bytes())
+            ));
+        }
+        for peer in &result.failover_peers {
+            failover_peer(&mut w, peer);
+        }
+        for c in &result.classes {
+            class(&mut w, c)?;
+        }
+        for s in &result.subclasses {
+            subclass(&mut w, s)?;
+        }
+    }
+    for l in result.leases.all() {
+        lease(&mut w, &l);
+    }
+    for h in &result.hosts {
+        host(&mut w, h)?;
+    }
+    if isc {
+        let byte_order = result.authoring_byte_order.unwrap_or_default();
+        for i in &result.ias {
+            ia(&mut w, i, byte_order);
+        }
+    }
+
+    Ok(w.out)
+}
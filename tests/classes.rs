@@ -0,0 +1,155 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::classes::ClassMatch;
+use crate::dhcpd_parser::config::PermitKind;
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::expr::Expression;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::LeasesMethods;
+
+#[test]
+fn spawned_subclasses_test() {
+    let res = parser::parse(
+        r#"
+class "omapi-class" {
+  dynamic;
+  lease limit 10;
+}
+
+subclass "allocation-class" 1:0:c:29:ab:cd:ef {
+  dynamic;
+  lease limit 2;
+}
+
+subclass "vendor-classes" "PXEClient:Arch:00000" {
+  deleted;
+}
+
+lease 10.0.0.5 {
+  starts 1 2019/01/07 10:00:00;
+  binding state active;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    assert_eq!(result.leases.all().len(), 1);
+
+    assert_eq!(result.classes.len(), 1);
+    assert_eq!(result.classes[0].name, "omapi-class");
+    assert_eq!(result.classes[0].lease_limit, Some(10));
+    assert!(result.classes[0].dynamic);
+
+    assert_eq!(result.subclasses.len(), 2);
+    let spawned = &result.subclasses[0];
+    assert_eq!(spawned.class, "allocation-class");
+    assert_eq!(spawned.value, vec![1, 0, 0x0c, 0x29, 0xab, 0xcd, 0xef]);
+    assert_eq!(spawned.lease_limit, Some(2));
+    assert!(spawned.dynamic);
+    assert!(!spawned.deleted);
+
+    let deleted = &result.subclasses[1];
+    assert_eq!(deleted.class, "vendor-classes");
+    assert_eq!(deleted.value, b"PXEClient:Arch:00000".to_vec());
+    assert!(deleted.deleted);
+    assert!(deleted.lease_limit.is_none());
+}
+
+#[test]
+fn invalid_lease_limit_test() {
+    let res = parser::parse(
+        r#"
+subclass "allocation-class" 1:0:c:29:ab:cd:ef {
+  lease limit many;
+}
+"#,
+    );
+
+    assert!(res.is_err());
+}
+
+const CONFIG: &str = r#"
+class "pxe" {
+  match if substring (option vendor-class-identifier, 0, 9) = "PXEClient";
+  filename "pxelinux.0";
+}
+class "vendor" {
+  match option vendor-class-identifier;
+}
+class "per-mac" {
+  match hardware;
+  spawn with hardware;
+  lease limit 2;
+}
+subclass "vendor" "MSFT 5.0";
+subclass "vendor" 1:aa:bb:cc:dd:ee:ff { lease limit 1; }
+
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  pool {
+    allow members of "pxe";
+    deny unknown-clients;
+    allow dynamic bootp clients;
+    deny after 4 2030/01/01 00:00:00;
+    allow booting;
+    range 10.0.0.10 10.0.0.20;
+  }
+}
+"#;
+
+#[test]
+fn class_match_and_permits_test() {
+    let res = parser::parse(CONFIG);
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    let classes = &result.classes;
+    assert_eq!(classes.len(), 3);
+    match &classes[0].matches {
+        Some(ClassMatch::If(e)) => assert_eq!(
+            e.to_string(),
+            "substring (option vendor-class-identifier, 0, 9) = \"PXEClient\""
+        ),
+        other => panic!("Expected match if, got {:?}", other),
+    }
+    assert_eq!(classes[0].statements.len(), 1);
+    assert_eq!(
+        classes[1].matches,
+        Some(ClassMatch::Value(Expression::Option(
+            "vendor-class-identifier".to_owned()
+        )))
+    );
+    assert_eq!(
+        classes[2].spawn_with,
+        Some(Expression::Name("hardware".to_owned()))
+    );
+    assert_eq!(classes[2].lease_limit, Some(2));
+
+    assert_eq!(result.subclasses.len(), 2);
+    assert_eq!(result.subclasses[0].value, b"MSFT 5.0".to_vec());
+    assert_eq!(
+        result.subclasses[1].value,
+        vec![1, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff]
+    );
+    assert_eq!(result.subclasses[1].lease_limit, Some(1));
+
+    let config = parser::parse_config(CONFIG).unwrap();
+    let pool = config.subnets()[0].pools()[0];
+    let permits: Vec<String> = pool.permits.iter().map(|p| p.to_string()).collect();
+    assert_eq!(
+        permits,
+        vec![
+            "allow members of \"pxe\";",
+            "deny unknown-clients;",
+            "allow dynamic bootp clients;",
+            "deny after 4 2030/01/01 00:00:00;",
+        ]
+    );
+    assert_eq!(
+        pool.permits[0].kind,
+        PermitKind::MembersOf("pxe".to_owned())
+    );
+    assert!(!pool.permits[1].allow);
+    assert_eq!(pool.statements.len(), 2);
+    assert_eq!(pool.ranges().len(), 1);
+}
@@ -1,5 +1,7 @@
 extern crate dhcpd_parser;
 
+use std::cmp::Ordering;
+
 use crate::dhcpd_parser::common::Date;
 
 #[test]
@@ -39,3 +41,15 @@ fn date_rfc3339() {
         "This doesn\'t seem like a correct RFC3339 date: \"T\"",
     );
 }
+
+#[test]
+fn date_ordering() {
+    let date = Date::from_rfc3339(1, "2019-03-01T00:00:00+00:00").unwrap();
+    let later = Date::from_rfc3339(1, "2019-03-01T00:00:01+00:00").unwrap();
+
+    assert_eq!(date.cmp(&date), Ordering::Equal);
+    assert!(date <= date);
+    assert!(date >= date);
+    assert!(date < later);
+    assert_eq!(vec![later, date, date].into_iter().max(), Some(later));
+}
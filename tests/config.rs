@@ -0,0 +1,117 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv4Addr;
+
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::options::OptionValue;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn config_syntax_tree_test() {
+    let res = parser::parse_config(
+        r#"
+default-lease-time 600;
+option domain-name "example.org";
+
+shared-network office {
+  subnet 10.11.5.0 netmask 255.255.255.0 {
+    range 10.11.5.50 10.11.5.99;
+    option routers 10.11.5.1;
+    pool {
+      range dynamic-bootp 10.11.5.100;
+    }
+  }
+}
+
+group {
+  host printer {
+    hardware ethernet 00:11:22:33:44:55;
+  }
+}
+
+class "pxe" {
+  match if substring (option vendor-class-identifier, 0, 9) = "PXEClient";
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let statements = res.unwrap().statements;
+    assert_eq!(statements.len(), 5);
+
+    match &statements[0].kind {
+        StatementKind::Parameter(p) => {
+            assert_eq!(p.name, "default-lease-time");
+            assert_eq!(p.args, vec!["600".to_owned()]);
+        }
+        other => panic!("Expected parameter, got {:?}", other),
+    }
+    assert_eq!(statements[0].span.line, 2);
+    assert_eq!(statements[0].span.column, 1);
+
+    match &statements[1].kind {
+        StatementKind::Option(o) => {
+            assert_eq!(o.name, "domain-name");
+            assert_eq!(o.code, Some(15));
+            assert_eq!(o.value, OptionValue::Text("example.org".to_owned()));
+        }
+        other => panic!("Expected option, got {:?}", other),
+    }
+
+    let shared = match &statements[2].kind {
+        StatementKind::SharedNetwork(s) => s,
+        other => panic!("Expected shared-network, got {:?}", other),
+    };
+    assert_eq!(shared.name, "office");
+    let subnet = match &shared.statements[0].kind {
+        StatementKind::Subnet(s) => s,
+        other => panic!("Expected subnet, got {:?}", other),
+    };
+    assert_eq!(subnet.network, Ipv4Addr::new(10, 11, 5, 0));
+    assert_eq!(subnet.netmask, Ipv4Addr::new(255, 255, 255, 0));
+    assert_eq!(subnet.statements.len(), 3);
+    match &subnet.statements[0].kind {
+        StatementKind::Range(r) => {
+            assert!(!r.dynamic_bootp);
+            assert_eq!(r.low, Ipv4Addr::new(10, 11, 5, 50));
+            assert_eq!(r.high, Ipv4Addr::new(10, 11, 5, 99));
+        }
+        other => panic!("Expected range, got {:?}", other),
+    }
+    match &subnet.statements[2].children()[0].kind {
+        StatementKind::Range(r) => {
+            assert!(r.dynamic_bootp);
+            assert_eq!(r.low, r.high);
+        }
+        other => panic!("Expected range, got {:?}", other),
+    }
+
+    match &statements[3].children()[0].kind {
+        StatementKind::Host(h) => assert_eq!(h.name, "printer"),
+        other => panic!("Expected host, got {:?}", other),
+    }
+
+    match &statements[4].kind {
+        StatementKind::Class(c) => {
+            assert_eq!(c.name, "pxe");
+            assert!(c.matches.is_some());
+            assert!(c.statements.is_empty());
+        }
+        other => panic!("Expected class, got {:?}", other),
+    }
+}
+
+#[test]
+fn config_error_location_test() {
+    let res = parser::parse_config(
+        "
+subnet 10.11.5.0 netmask 255.255.255.0 {
+  range 10.11.5.50 10.11.5;
+}",
+    );
+
+    assert_eq!(
+        res.unwrap_err(),
+        "line 3, column 20: Invalid range end \"10.11.5\""
+    );
+}
@@ -0,0 +1,137 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::cst;
+use crate::dhcpd_parser::cst::TextEdit;
+use crate::dhcpd_parser::cst::TriviaKind;
+
+const CONFIG: &str = "# dhcpd.conf for the office\r
+\r
+authoritative;\r
+default-lease-time 600;   # ten minutes\r
+\r
+subnet 10.0.0.0 netmask 255.255.255.0 {\r
+\toption routers 10.0.0.1;\r
+\toption domain-name \"office # 1\";\r
+\tdefault-lease-time 3600;\r
+}\r
+\r
+subnet 10.0.1.0 netmask 255.255.255.0 {\r
+  # lab machines come and go\r
+  default-lease-time    600;\r
+  if option vendor-class-identifier = \"PXEClient\" {\r
+    filename \"pxelinux.0\";\r
+  } else { }\r
+}\r
+key ddns { algorithm hmac-md5; secret \"c2VjcmV0\"; };\r
+# end";
+
+#[test]
+fn lossless_round_trip_test() {
+    let res = cst::parse_document(CONFIG);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let document = res.unwrap();
+
+    assert_eq!(document.to_string(), CONFIG);
+    assert_eq!(document.syntax().to_string(), CONFIG);
+
+    let syntax = document.syntax();
+    assert_eq!(syntax.statements.len(), 5);
+    assert_eq!(syntax.trailing.len(), 2);
+    assert_eq!(syntax.trailing[1].kind, TriviaKind::Comment);
+    assert_eq!(syntax.trailing[1].text, "# end");
+
+    let first = syntax.statements[0].tokens()[0];
+    assert_eq!(first.text, "authoritative");
+    assert_eq!(first.leading[0].kind, TriviaKind::Comment);
+    assert_eq!(first.leading[0].text, "# dhcpd.conf for the office\r");
+
+    let subnet = &syntax.statements[3];
+    assert_eq!(subnet.keyword(), "subnet");
+    assert_eq!(subnet.children().len(), 2);
+    assert_eq!(subnet.children()[1].keyword(), "if");
+    assert_eq!(subnet.children()[1].children().len(), 1);
+    assert_eq!(syntax.statements[4].keyword(), "key");
+    assert_eq!(syntax.statements[4].tokens().last().unwrap().text, ";");
+
+    // Every typed statement maps to the syntax node it was parsed from.
+    for statement in document.config().statements.iter() {
+        let node = document.node(statement).unwrap();
+        assert_eq!(node.span().start, statement.span.start);
+    }
+}
+
+#[test]
+fn edit_lease_time_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+
+    let subnet = document.config().subnets()[1];
+    let parameter = subnet
+        .statements
+        .iter()
+        .find(|s| match &s.kind {
+            StatementKind::Parameter(p) => p.name == "default-lease-time",
+            _ => false,
+        })
+        .unwrap();
+    let node = document.node(parameter).unwrap();
+    let value = node.tokens()[1].span.clone();
+
+    document.apply(&TextEdit::replace(&value, "7200")).unwrap();
+    assert_eq!(
+        document.to_string(),
+        CONFIG.replace("default-lease-time    600;", "default-lease-time    7200;")
+    );
+    assert_eq!(document.syntax().to_string(), document.to_string());
+    assert_eq!(document.config().parameters.default_lease_time, Some(600));
+
+    let subnet = document.config().subnets()[1];
+    match &subnet.statements[0].kind {
+        StatementKind::Parameter(p) => assert_eq!(p.args, vec!["7200"]),
+        other => panic!("unexpected statement {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_edit_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+    let subnet = document.syntax().statements[3].span();
+
+    let res = document.apply(&TextEdit::replace(&subnet, "subnet 10.0.0.0 {"));
+    assert_eq!(
+        res.err(),
+        Some("line 12, column 17: Expected 'netmask', got Some(Paren('{'))".to_owned())
+    );
+    assert_eq!(document.to_string(), CONFIG);
+
+    let res = document.apply(&TextEdit::insert(CONFIG.len() + 1, ";"));
+    assert_eq!(
+        res.err(),
+        Some(format!(
+            "Invalid edit range {}..{} for a document of {} bytes",
+            CONFIG.len() + 1,
+            CONFIG.len() + 1,
+            CONFIG.len()
+        ))
+    );
+}
+
+#[test]
+fn secret_redacted_test() {
+    let document =
+        cst::parse_document("key ddns { algorithm hmac-md5; secret pRP5FapFoJ95JEL06sv4PQ==; }\n")
+            .unwrap();
+
+    for debug in [
+        format!("{:?}", document),
+        format!("{:#?}", document),
+        format!("{:?}", document.syntax()),
+    ]
+    .iter()
+    {
+        assert!(!debug.contains("pRP5FapFoJ95JEL06sv4PQ"), "{}", debug);
+        assert!(debug.contains("<redacted>"));
+    }
+    assert!(document.to_string().contains("pRP5FapFoJ95JEL06sv4PQ=="));
+}
@@ -0,0 +1,99 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn key_and_zone_test() {
+    let res = parser::parse_config(
+        r#"
+key ddns-key {
+  algorithm hmac-md5;
+  secret "c2VjcmV0LXZhbHVl";
+};
+
+zone example.com. {
+  primary 127.0.0.1;
+  key ddns-key;
+}
+
+zone "5.11.10.in-addr.arpa." {
+  primary 10.11.5.1;
+  secondary 10.11.5.2;
+  key "ddns-key";
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let keys = config.keys();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].name, "ddns-key");
+    assert_eq!(keys[0].algorithm.as_deref(), Some("hmac-md5"));
+    let secret = keys[0].secret.as_ref().unwrap();
+    assert_eq!(secret.expose(), "c2VjcmV0LXZhbHVl");
+
+    let zones = config.zones();
+    assert_eq!(zones.len(), 2);
+    assert_eq!(zones[0].name, "example.com.");
+    assert_eq!(zones[0].primary.as_deref(), Some("127.0.0.1"));
+    assert_eq!(zones[0].tsig_key(&config), Some(keys[0]));
+    assert_eq!(zones[1].secondary.as_deref(), Some("10.11.5.2"));
+    assert_eq!(zones[1].key.as_deref(), Some("ddns-key"));
+}
+
+#[test]
+fn secret_redacted_test() {
+    let config = parser::parse_config(
+        r#"
+key omapi-key {
+  algorithm hmac-sha256;
+  secret "dG9wLXNlY3JldA==";
+}
+"#,
+    )
+    .unwrap();
+
+    let key = config.keys()[0];
+    let debug = format!("{:?}", config);
+    assert!(!debug.contains("dG9wLXNlY3JldA=="));
+    assert!(debug.contains("<redacted>"));
+    assert_eq!(key.secret.as_ref().unwrap().to_string(), "<redacted>");
+}
+
+#[test]
+fn unquoted_secret_test() {
+    let res =
+        parser::parse_config("key k { algorithm hmac-md5; secret pRP5FapFoJ95JEL06sv4PQ==; }\n");
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let key = config.keys()[0];
+    assert_eq!(key.algorithm.as_deref(), Some("hmac-md5"));
+    assert_eq!(
+        key.secret.as_ref().unwrap().expose(),
+        "pRP5FapFoJ95JEL06sv4PQ=="
+    );
+}
+
+#[test]
+fn unknown_zone_key_test() {
+    // The key is usually declared in an included file.
+    let res = parser::parse_config(
+        r#"
+include "ddns-key.conf";
+zone example.com. {
+  primary 127.0.0.1;
+  key ddns-key;
+}
+"#,
+    );
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let zone = config.zones()[0];
+    assert_eq!(zone.key.as_deref(), Some("ddns-key"));
+    assert_eq!(zone.tsig_key(&config), None);
+}
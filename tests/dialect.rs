@@ -0,0 +1,113 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::dialect::Dialect;
+use crate::dhcpd_parser::leases::BindingState;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::LeasesMethods;
+use crate::dhcpd_parser::parser::ParserOptions;
+
+const OPENBSD_LEASES: &str = r#"
+lease 192.168.0.2 {
+  starts 2 2019/01/01 22:00:00 UTC;
+  ends 2 2019/01/01 23:00:00 UTC;
+  hardware ethernet 11:11:11:11:11:11;
+  uid 01:11:11:11:11:11:11;
+  client-hostname "laptop";
+  hostname "laptop.example.org";
+  abandoned;
+}
+"#;
+
+const ISC_LEASES: &str = r#"
+lease 192.168.0.3 {
+  starts 2 2019/01/01 22:00:00;
+  cltt 2 2019/01/01 22:00:00;
+  binding state abandoned;
+  next binding state free;
+  hardware ethernet 22:22:22:22:22:22;
+}
+"#;
+
+fn options(dialect: Dialect, strict: bool) -> ParserOptions {
+    ParserOptions::new().dialect(dialect).strict_dialect(strict)
+}
+
+#[test]
+fn auto_detect_test() {
+    let res = parser::parse(OPENBSD_LEASES);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    assert_eq!(result.dialect, Some(Dialect::OpenBsd));
+    assert_eq!(
+        result.leases.all()[0].binding_state,
+        BindingState::Abandoned
+    );
+
+    let result = parser::parse(ISC_LEASES).unwrap();
+    assert_eq!(result.dialect, Some(Dialect::Isc));
+    assert_eq!(
+        result.leases.all()[0].binding_state,
+        BindingState::Abandoned
+    );
+
+    let config = parser::parse_config("option routers 10.0.0.1;\n").unwrap();
+    assert_eq!(config.dialect, None);
+}
+
+#[test]
+fn lenient_dialect_test() {
+    let res = parser::parse_with(ISC_LEASES, &options(Dialect::OpenBsd, false));
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(res.unwrap().dialect, Some(Dialect::OpenBsd));
+
+    let res = parser::parse_with(OPENBSD_LEASES, &options(Dialect::Isc, false));
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+}
+
+#[test]
+fn strict_dialect_test() {
+    let res = parser::parse_with(OPENBSD_LEASES, &options(Dialect::OpenBsd, true));
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+
+    let res = parser::parse_with(ISC_LEASES, &options(Dialect::OpenBsd, true));
+    assert_eq!(
+        res.err(),
+        Some("line 4, column 21: cltt is not supported by OpenBSD dhcpd".to_owned())
+    );
+
+    let res = parser::parse_with(OPENBSD_LEASES, &options(Dialect::Isc, true));
+    assert_eq!(
+        res.err(),
+        Some("line 8, column 12: hostname is not supported by ISC dhcpd".to_owned())
+    );
+
+    let res = parser::parse_config_with(
+        r#"
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  pool {
+    range 10.0.0.10 10.0.0.20;
+  }
+}
+"#,
+        &options(Dialect::OpenBsd, true),
+    );
+    assert_eq!(
+        res.err(),
+        Some("line 3, column 3: pool is not supported by OpenBSD dhcpd".to_owned())
+    );
+}
+
+#[test]
+fn strict_auto_detect_test() {
+    let mixed = format!("{}{}", OPENBSD_LEASES, ISC_LEASES);
+
+    let res = parser::parse_with(mixed.as_str(), &options(Dialect::Auto, false));
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(res.unwrap().leases.all().len(), 2);
+
+    let res = parser::parse_with(mixed.as_str(), &options(Dialect::Auto, true));
+    assert_eq!(
+        res.err(),
+        Some("line 14, column 21: cltt is not supported by OpenBSD dhcpd".to_owned())
+    );
+}
@@ -0,0 +1,73 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::duid::Duid;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn duid_variants_test() {
+    let llt = Duid::from_hex("00:01:00:01:2b:8e:16:d6:30:de:4b:03:a9:89").unwrap();
+    assert_eq!(
+        llt,
+        Duid::Llt {
+            hardware_type: 1,
+            time: 0x2b8e_16d6,
+            link_layer_address: vec![0x30, 0xde, 0x4b, 0x03, 0xa9, 0x89],
+        }
+    );
+    assert_eq!(llt.mac().as_deref(), Some("30:de:4b:03:a9:89"));
+    assert_eq!(llt.to_string(), "00:01:00:01:2b:8e:16:d6:30:de:4b:03:a9:89");
+
+    let en = Duid::from_hex("00:02:00:00:00:09:0c:c0:84:d3:03:00:09:12").unwrap();
+    assert_eq!(
+        en,
+        Duid::En {
+            enterprise_number: 9,
+            identifier: vec![0x0c, 0xc0, 0x84, 0xd3, 0x03, 0x00, 0x09, 0x12],
+        }
+    );
+    assert!(en.mac().is_none());
+
+    let ll = Duid::from_hex("00:03:00:01:aa:bb:cc:dd:ee:ff").unwrap();
+    assert_eq!(ll.mac().as_deref(), Some("aa:bb:cc:dd:ee:ff"));
+
+    let uuid = Duid::from_bytes(&[0, 4, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+    assert_eq!(
+        uuid,
+        Duid::Uuid([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16])
+    );
+    assert_eq!(
+        uuid.to_string(),
+        "00:04:01:02:03:04:05:06:07:08:09:0a:0b:0c:0d:0e:0f:10"
+    );
+
+    assert_eq!(Duid::from_bytes(&[0, 9, 1]), Duid::Unknown(vec![0, 9, 1]));
+    assert!(Duid::from_hex("00:zz").is_err());
+}
+
+#[test]
+fn server_and_client_duid_test() {
+    let res = parser::parse(
+        r#"
+server-duid "\000\001\000\001+\216\026\3260\336K\003\251\211";
+
+ia-na "\001\000\000\000\000\003\000\001RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+
+    let server_duid = result.server_duid.unwrap();
+    assert_eq!(
+        server_duid.to_string(),
+        "00:01:00:01:2b:8e:16:d6:30:de:4b:03:a9:89"
+    );
+    assert_eq!(server_duid.mac().as_deref(), Some("30:de:4b:03:a9:89"));
+
+    assert_eq!(
+        result.ias[0].duid.mac().as_deref(),
+        Some("52:54:00:12:34:56")
+    );
+}
@@ -0,0 +1,271 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::config::ScopeEntry;
+use crate::dhcpd_parser::config::ScopePath;
+use crate::dhcpd_parser::cst;
+use crate::dhcpd_parser::cst::TextEdit;
+use crate::dhcpd_parser::parser::Host;
+
+const CONFIG: &str = r#"# Office network
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  option routers 10.0.0.1;
+
+  # Alice's laptop
+  host alice {
+    hardware ethernet 00:11:22:33:44:55;
+    fixed-address 10.0.0.10;   # desk
+  }
+}
+
+subnet 10.0.1.0 netmask 255.255.255.0 {
+	option routers 10.0.1.1;
+}
+
+group {
+  host printer { hardware ethernet 00:aa:bb:cc:dd:ee; fixed-address 10.0.0.20; }
+}
+"#;
+
+fn subnet(network: &str) -> ScopePath {
+    ScopePath {
+        entries: vec![ScopeEntry::Subnet {
+            network: network.parse().unwrap(),
+            prefix_len: 24,
+        }],
+    }
+}
+
+fn host(name: &str, mac: &str, address: &str) -> Host {
+    Host {
+        name: name.to_owned(),
+        hardware_type: Some("ethernet".to_owned()),
+        mac: Some(mac.to_owned()),
+        fixed_addresses: vec![address.to_owned()],
+        ..Host::default()
+    }
+}
+
+#[test]
+fn add_host_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+
+    let edits = document
+        .add_host(
+            &subnet("10.0.1.0"),
+            &host("bob", "00:11:22:33:44:66", "10.0.1.10"),
+        )
+        .unwrap();
+    let text = "
+\thost \"bob\" {
+\t\thardware ethernet 00:11:22:33:44:66;
+\t\tfixed-address 10.0.1.10;
+\t}";
+    let offset = CONFIG.find("10.0.1.1;").unwrap() + "10.0.1.1;".len();
+    assert_eq!(edits, vec![TextEdit::insert(offset, text)]);
+    assert_eq!(
+        document.to_string(),
+        format!("{}{}{}", &CONFIG[..offset], text, &CONFIG[offset..])
+    );
+    let bob = document.config().subnets()[1].hosts()[0].clone();
+    assert_eq!(bob.name, "bob");
+    assert_eq!(
+        bob.fixed_address_on_subnet(),
+        Some("10.0.1.10".parse().unwrap())
+    );
+
+    let mut document = cst::parse_document(CONFIG).unwrap();
+    let scope = subnet("10.0.1.0");
+    let errors = vec![
+        (
+            host("alice", "00:11:22:33:44:77", "10.0.1.11"),
+            "Host \"alice\" already exists",
+        ),
+        (
+            host("carol", "00:AA:BB:CC:DD:EE", "10.0.1.11"),
+            "MAC address 00:AA:BB:CC:DD:EE is already used by host \"printer\"",
+        ),
+        (
+            host("carol", "00:11:22:33:44:77", "10.0.0.10"),
+            "Address 10.0.0.10 is already used by host \"alice\"",
+        ),
+        (
+            host("carol", "00:11:22:33:44:77", "10.0.0.11"),
+            "Host \"carol\" has no fixed-address on subnet 10.0.1.0/24",
+        ),
+        (
+            host("carol", "00-11-22-33-44-77", "10.0.1.11"),
+            "Invalid MAC address \"00-11-22-33-44-77\"",
+        ),
+    ];
+    for (h, err) in errors {
+        assert_eq!(document.add_host(&scope, &h).err(), Some(err.to_owned()));
+    }
+    assert_eq!(
+        document
+            .add_host(
+                &subnet("10.0.9.0"),
+                &host("carol", "00:11:22:33:44:77", "10.0.9.1")
+            )
+            .err(),
+        Some("Unknown scope \"subnet 10.0.9.0/24\"".to_owned())
+    );
+    assert_eq!(document.to_string(), CONFIG);
+}
+
+#[test]
+fn remove_host_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+
+    document.remove_host("alice").unwrap();
+    assert_eq!(
+        document.to_string(),
+        CONFIG.replace(
+            "  # Alice's laptop
+  host alice {
+    hardware ethernet 00:11:22:33:44:55;
+    fixed-address 10.0.0.10;   # desk
+  }
+",
+            ""
+        )
+    );
+    assert_eq!(document.config().hosts().len(), 1);
+    assert_eq!(document.config().hosts()[0].name, "printer");
+
+    assert_eq!(
+        document.remove_host("alice").err(),
+        Some("Unknown host \"alice\"".to_owned())
+    );
+}
+
+#[test]
+fn update_host_mac_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+
+    let edits = document
+        .update_host_mac("printer", "00:aa:bb:cc:dd:ff")
+        .unwrap();
+    let start = CONFIG.find("00:aa:bb:cc:dd:ee").unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit {
+            start,
+            end: start + 17,
+            text: "00:aa:bb:cc:dd:ff".to_owned(),
+        }]
+    );
+    assert_eq!(
+        document.to_string(),
+        CONFIG.replace("00:aa:bb:cc:dd:ee", "00:aa:bb:cc:dd:ff")
+    );
+
+    assert_eq!(
+        document
+            .update_host_mac("printer", "00:11:22:33:44:55")
+            .err(),
+        Some("MAC address 00:11:22:33:44:55 is already used by host \"alice\"".to_owned())
+    );
+
+    let mut document = cst::parse_document("host a {\n  fixed-address 10.0.0.1;\n}\n").unwrap();
+    document.update_host_mac("a", "00:11:22:33:44:55").unwrap();
+    assert_eq!(
+        document.to_string(),
+        "host a {\n  fixed-address 10.0.0.1;\n  hardware ethernet 00:11:22:33:44:55;\n}\n"
+    );
+}
+
+#[test]
+fn move_host_test() {
+    let mut document = cst::parse_document(CONFIG).unwrap();
+
+    assert_eq!(
+        document.move_host("alice", &subnet("10.0.1.0")).err(),
+        Some("Host \"alice\" has no fixed-address on subnet 10.0.1.0/24".to_owned())
+    );
+
+    let group = ScopePath {
+        entries: vec![ScopeEntry::Group(None)],
+    };
+    document.move_host("alice", &group).unwrap();
+    assert_eq!(
+        document.to_string(),
+        r#"# Office network
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  option routers 10.0.0.1;
+
+}
+
+subnet 10.0.1.0 netmask 255.255.255.0 {
+	option routers 10.0.1.1;
+}
+
+group {
+  host printer { hardware ethernet 00:aa:bb:cc:dd:ee; fixed-address 10.0.0.20; }
+  # Alice's laptop
+  host alice {
+    hardware ethernet 00:11:22:33:44:55;
+    fixed-address 10.0.0.10;   # desk
+  }
+}
+"#
+    );
+    let hosts = document.config().hosts();
+    assert_eq!(hosts.len(), 2);
+    assert_eq!(hosts[1].name, "alice");
+    assert_eq!(hosts[1].scope, group);
+
+    document
+        .move_host("printer", &ScopePath::default())
+        .unwrap();
+    assert!(document.to_string().ends_with(
+        "}\nhost printer { hardware ethernet 00:aa:bb:cc:dd:ee; fixed-address 10.0.0.20; }\n"
+    ));
+    let hosts = document.config().hosts();
+    assert_eq!(hosts[1].name, "printer");
+    assert_eq!(hosts[1].scope, ScopePath::default());
+}
+
+#[test]
+fn trailing_comment_test() {
+    let config = "group {\n\thost a { fixed-address 10.0.0.1; } # old printer\n\thost b { fixed-address 10.0.0.2; }\n}\n";
+
+    let mut document = cst::parse_document(config).unwrap();
+    document.remove_host("a").unwrap();
+    assert_eq!(
+        document.to_string(),
+        "group {\n\thost b { fixed-address 10.0.0.2; }\n}\n"
+    );
+
+    let mut document = cst::parse_document(config).unwrap();
+    document.move_host("a", &ScopePath::default()).unwrap();
+    assert_eq!(
+        document.to_string(),
+        "group {\n\thost b { fixed-address 10.0.0.2; }\n}\nhost a { fixed-address 10.0.0.1; } # old printer\n"
+    );
+}
+
+#[test]
+fn leading_comment_test() {
+    let config = "group {\n\thost a { fixed-address 10.0.0.1; }\n\n\t# Bob's desk\n\t# room 2\n\thost b { fixed-address 10.0.0.2; }\n}\n";
+
+    let mut document = cst::parse_document(config).unwrap();
+    document.remove_host("b").unwrap();
+    assert_eq!(
+        document.to_string(),
+        "group {\n\thost a { fixed-address 10.0.0.1; }\n\n}\n"
+    );
+
+    let mut document = cst::parse_document(config).unwrap();
+    document.move_host("b", &ScopePath::default()).unwrap();
+    assert_eq!(
+        document.to_string(),
+        "group {\n\thost a { fixed-address 10.0.0.1; }\n\n}\n# Bob's desk\n# room 2\nhost b { fixed-address 10.0.0.2; }\n"
+    );
+
+    // A blank line separates the comment from the host.
+    let config = "# Hosts\n\nhost a { fixed-address 10.0.0.1; }\n";
+    let mut document = cst::parse_document(config).unwrap();
+    document.remove_host("a").unwrap();
+    assert_eq!(document.to_string(), "# Hosts\n\n");
+}
@@ -0,0 +1,123 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::OnEvent;
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::expr::BinaryOp;
+use crate::dhcpd_parser::expr::Expression;
+use crate::dhcpd_parser::options::OptionValue;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn if_statement_test() {
+    let res = parser::parse_config(
+        r#"
+if substring (option vendor-class-identifier, 0, 9) = "PXEClient" {
+  filename "pxelinux.0";
+  if exists user-class and option user-class = "iPXE" {
+    filename "boot.ipxe";
+  }
+} elsif not known {
+  option host-name = concat ("guest-", binary-to-ascii (16, 8, "-", substring (hardware, 1, 6)));
+} else {
+  default-lease-time 600;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let statements = res.unwrap().statements;
+    assert_eq!(statements.len(), 1);
+
+    let cond = match &statements[0].kind {
+        StatementKind::If(v) => v,
+        other => panic!("Expected if, got {:?}", other),
+    };
+    assert_eq!(cond.branches.len(), 2);
+    assert_eq!(
+        cond.branches[0].condition,
+        Expression::Binary {
+            op: BinaryOp::Equal,
+            left: Box::new(Expression::Call {
+                name: "substring".to_owned(),
+                args: vec![
+                    Expression::Option("vendor-class-identifier".to_owned()),
+                    Expression::Number(0),
+                    Expression::Number(9),
+                ],
+            }),
+            right: Box::new(Expression::Str("PXEClient".to_owned())),
+        }
+    );
+    assert_eq!(cond.branches[0].statements.len(), 2);
+    match &cond.branches[0].statements[1].kind {
+        StatementKind::If(v) => assert_eq!(
+            v.branches[0].condition.to_string(),
+            "exists user-class and option user-class = \"iPXE\""
+        ),
+        other => panic!("Expected if, got {:?}", other),
+    }
+
+    assert_eq!(
+        cond.branches[1].condition,
+        Expression::Not(Box::new(Expression::Name("known".to_owned())))
+    );
+    match &cond.branches[1].statements[0].kind {
+        StatementKind::Option(o) => match &o.value {
+            OptionValue::Expression(e) => assert_eq!(
+                e.to_string(),
+                "concat (\"guest-\", binary-to-ascii (16, 8, \"-\", substring (hardware, 1, 6)))"
+            ),
+            other => panic!("Expected expression, got {:?}", other),
+        },
+        other => panic!("Expected option, got {:?}", other),
+    }
+    assert_eq!(cond.else_statements.as_ref().unwrap().len(), 1);
+}
+
+#[test]
+fn on_event_test() {
+    let res = parser::parse_config(
+        r#"
+on commit or release {
+  set clip = binary-to-ascii (10, 8, ".", leased-address);
+  if option dhcp-message-type = 3 {
+    unset clip;
+  }
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let statements = res.unwrap().statements;
+    let on = match &statements[0].kind {
+        StatementKind::On(v) => v,
+        other => panic!("Expected on, got {:?}", other),
+    };
+    assert_eq!(on.events, vec![OnEvent::Commit, OnEvent::Release]);
+    match &on.statements[0].kind {
+        StatementKind::Set(v) => {
+            assert_eq!(v.name, "clip");
+            assert_eq!(
+                v.value.to_string(),
+                "binary-to-ascii (10, 8, \".\", leased-address)"
+            );
+        }
+        other => panic!("Expected set, got {:?}", other),
+    }
+    match &on.statements[1].kind {
+        StatementKind::If(v) => match &v.branches[0].statements[0].kind {
+            StatementKind::Unset(name) => assert_eq!(name, "clip"),
+            other => panic!("Expected unset, got {:?}", other),
+        },
+        other => panic!("Expected if, got {:?}", other),
+    }
+}
+
+#[test]
+fn expression_errors_test() {
+    let res = parser::parse_config("if substring (option host-name, 0) = \"a\" { }\n");
+    assert_eq!(
+        res.err(),
+        Some("line 1, column 34: substring expects 3 arguments, got 2".to_owned())
+    );
+}
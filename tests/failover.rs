@@ -0,0 +1,213 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::failover::FailoverRole;
+use crate::dhcpd_parser::failover::FailoverState;
+use crate::dhcpd_parser::include::MemoryLoader;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::LeasesMethods;
+
+#[test]
+fn failover_peer_state_test() {
+    let res = parser::parse(
+        r#"
+failover peer "dhcp-failover" state {
+  my state communications-interrupted at 1 2019/01/07 10:11:12;
+  partner state normal at 5 2019/01/04 22:00:00;
+  mclt 3600;
+}
+
+lease 10.0.0.5 {
+  starts 1 2019/01/07 10:00:00;
+  binding state active;
+}
+
+failover peer "other" state {
+  my state partner-down at 1 2019/01/07 10:11:12;
+  partner state unknown-state;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    assert_eq!(result.leases.all().len(), 1);
+    assert_eq!(result.failover_peers.len(), 2);
+
+    let peer = &result.failover_peers[0];
+    assert_eq!(peer.name, "dhcp-failover");
+    let my_state = peer.my_state.as_ref().unwrap();
+    assert_eq!(my_state.state, FailoverState::CommunicationsInterrupted);
+    assert_eq!(
+        my_state.at.unwrap().to_string(),
+        "Monday 2019/01/07 10:11:12"
+    );
+    assert_eq!(
+        peer.partner_state.as_ref().unwrap().state,
+        FailoverState::Normal
+    );
+    assert_eq!(peer.mclt, Some(3600));
+
+    let other = &result.failover_peers[1];
+    assert_eq!(
+        other.my_state.as_ref().unwrap().state,
+        FailoverState::PartnerDown
+    );
+    let partner_state = other.partner_state.as_ref().unwrap();
+    assert_eq!(partner_state.state, FailoverState::UnknownState);
+    assert!(partner_state.at.is_none());
+    assert!(other.mclt.is_none());
+}
+
+#[test]
+fn invalid_failover_state_test() {
+    let res = parser::parse(
+        r#"
+failover peer "dhcp-failover" state {
+  my state sleeping at 1 2019/01/07 10:11:12;
+}
+"#,
+    );
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn failover_peer_config_is_not_state_test() {
+    // The dhcpd.conf declaration has no `state` keyword and must be tolerated.
+    let res = parser::parse(
+        r#"
+failover peer "dhcp-failover" {
+  primary;
+  address 10.0.0.1;
+  port 647;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert!(res.unwrap().failover_peers.is_empty());
+}
+
+const PRIMARY: &str = r#"
+failover peer "dhcp-failover" {
+  primary;
+  address 10.0.0.2;
+  port 647;
+  peer address 10.0.0.3;
+  peer port 847;
+  max-response-delay 60;
+  max-unacked-updates 10;
+  mclt 3600;
+  split 128;
+  load balance max seconds 3;
+}
+
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  pool {
+    failover peer "dhcp-failover";
+    range 10.0.0.100 10.0.0.200;
+  }
+}
+"#;
+
+const SECONDARY: &str = r#"
+failover peer "dhcp-failover" {
+  secondary;
+  address 10.0.0.3;
+  port 847;
+  peer address 10.0.0.2;
+  peer port 647;
+  max-response-delay 60;
+  max-unacked-updates 10;
+  load balance max seconds 3;
+}
+"#;
+
+#[test]
+fn failover_peer_config_test() {
+    let res = parser::parse_config(PRIMARY);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let peers = config.failover_peers();
+    assert_eq!(peers.len(), 1);
+    let primary = peers[0];
+    assert_eq!(primary.name, "dhcp-failover");
+    assert_eq!(primary.role, Some(FailoverRole::Primary));
+    assert_eq!(primary.address.as_deref(), Some("10.0.0.2"));
+    assert_eq!(primary.port, Some(647));
+    assert_eq!(primary.peer_address.as_deref(), Some("10.0.0.3"));
+    assert_eq!(primary.peer_port, Some(847));
+    assert_eq!(primary.mclt, Some(3600));
+    assert_eq!(primary.split, Some(128));
+    assert_eq!(primary.load_balance_max_seconds, Some(3));
+
+    let pool = config.subnets()[0].pools()[0];
+    assert_eq!(pool.failover_peer.as_deref(), Some("dhcp-failover"));
+    assert_eq!(pool.peer(&config), Some(primary));
+
+    let secondary_config = parser::parse_config(SECONDARY).unwrap();
+    let secondary = secondary_config.failover_peers()[0];
+    assert!(primary.mismatches(secondary).is_empty());
+
+    let mut wrong = secondary.clone();
+    wrong.peer_port = Some(648);
+    wrong.role = Some(FailoverRole::Primary);
+    assert_eq!(
+        primary.mismatches(&wrong),
+        vec![
+            "roles Some(Primary) and Some(Primary) are not complementary".to_owned(),
+            "port Some(647) != partner's peer port Some(648)".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn unknown_failover_peer_reference_test() {
+    let input = r#"
+include "failover.conf";
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  pool {
+    failover peer "fo";
+    range 10.0.0.100 10.0.0.200;
+  }
+}
+"#;
+
+    // Without a loader the peer may be declared in the included file.
+    let res = parser::parse_config(input);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let pool = config.subnets()[0].pools()[0];
+    assert_eq!(pool.failover_peer.as_deref(), Some("fo"));
+    assert_eq!(pool.peer(&config), None);
+
+    let mut loader = MemoryLoader::new();
+    loader.insert("dhcpd.conf", input);
+    loader.insert("failover.conf", "failover peer \"fo\" { primary; }\n");
+    let res = parser::parse_config_file("dhcpd.conf", &loader);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let pool = config.subnets()[0].pools()[0];
+    assert_eq!(pool.peer(&config).unwrap().name, "fo");
+
+    loader.insert("failover.conf", "");
+    assert_eq!(
+        parser::parse_config_file("dhcpd.conf", &loader).err(),
+        Some("dhcpd.conf, line 5, column 19: Unknown failover peer \"fo\"".to_owned())
+    );
+}
+
+#[test]
+fn failover_split_test() {
+    let res = parser::parse_config(PRIMARY.replace("split 128;", "split 256;"));
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(res.unwrap().failover_peers()[0].split, Some(256));
+
+    let res = parser::parse_config(PRIMARY.replace("split 128;", "split 257;"));
+    assert_eq!(
+        res.err(),
+        Some("line 11, column 9: Invalid split value \"257\"".to_owned())
+    );
+}
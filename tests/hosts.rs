@@ -1,5 +1,9 @@
 extern crate dhcpd_parser;
 
+use std::net::Ipv6Addr;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::options::OptionValue;
 use crate::dhcpd_parser::parser;
 use crate::dhcpd_parser::parser::LeasesMethods;
 
@@ -85,3 +89,180 @@ host top-level-infra {
 
     assert!(hosts.iter().any(|h| h.name == "top-level-infra"));
 }
+
+#[test]
+fn omapi_host_lifecycle() {
+    // OMAPI re-emits changed hosts and marks removed ones with `deleted;`.
+    // The last entry for a name wins.
+    let res = parser::parse(
+        r#"
+host printer {
+  dynamic;
+  hardware ethernet 00:11:22:33:44:55;
+  fixed-address 10.0.0.10;
+}
+
+host laptop {
+  dynamic;
+  hardware ethernet 00:11:22:33:44:66;
+  fixed-address 10.0.0.20;
+}
+
+host printer {
+  dynamic;
+  hardware ethernet 00:11:22:33:44:55;
+  fixed-address 10.0.0.11;
+}
+
+host laptop {
+  dynamic;
+  deleted;
+}
+
+host phone {
+  dynamic;
+  deleted;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    assert_eq!(result.hosts.len(), 5);
+    assert!(result.hosts.iter().all(|h| h.dynamic));
+    assert!(result.hosts[3].deleted);
+
+    let live = result.live_hosts();
+    assert_eq!(live.len(), 1);
+    assert_eq!(live[0].name, "printer");
+    assert_eq!(live[0].fixed_addresses, vec!["10.0.0.11".to_owned()]);
+    assert!(!live[0].deleted);
+}
+
+#[test]
+fn complete_host_declaration() {
+    let res = parser::parse(
+        r#"
+host voip-phone {
+  hardware ethernet 00:0B:82:01:02:03;
+  uid 1:0:b:82:1:2:3;
+  fixed-address 10.11.5.20, 10.11.6.20;
+  ddns-hostname "phone-1";
+  option host-name "phone-1.example.org";
+  option routers 10.11.5.1;
+  filename "phone.cfg";
+  next-server tftp.example.org;
+  default-lease-time 86400;
+}
+
+host server6 {
+  host-identifier option dhcp6.client-id 0:1:0:1:1c:39:cf:88:8:0:27:fe:8f:95;
+  fixed-address6 2001:db8::10, 2001:db8::11;
+  fixed-prefix6 2001:db8:1::/48;
+}
+
+host relayed {
+  host-identifier v6relopt 1 dhcp6.interface-id "port1";
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let hosts = res.unwrap().hosts;
+    assert_eq!(hosts.len(), 3);
+
+    let phone = &hosts[0];
+    assert_eq!(phone.hardware_type.as_deref(), Some("ethernet"));
+    assert_eq!(phone.mac.as_deref(), Some("00:0b:82:01:02:03"));
+    assert_eq!(phone.uid, Some(vec![1, 0, 0xb, 0x82, 1, 2, 3]));
+    assert_eq!(phone.fixed_addresses, vec!["10.11.5.20", "10.11.6.20"]);
+    assert_eq!(phone.ddns_hostname.as_deref(), Some("phone-1"));
+    assert_eq!(phone.host_name(), Some("phone-1.example.org"));
+    assert_eq!(phone.filename.as_deref(), Some("phone.cfg"));
+    assert_eq!(phone.next_server.as_deref(), Some("tftp.example.org"));
+    assert_eq!(phone.options().len(), 2);
+    assert_eq!(phone.statements.len(), 3);
+
+    let server = &hosts[1];
+    let identifier = server.host_identifier.as_ref().unwrap();
+    assert_eq!(identifier.option.full_name(), "dhcp6.client-id");
+    assert_eq!(identifier.v6relopt, None);
+    match &identifier.option.value {
+        OptionValue::Data(v) => assert_eq!(v.len(), 14),
+        other => panic!("Expected data, got {:?}", other),
+    }
+    assert_eq!(
+        server.fixed_addresses6,
+        vec![
+            "2001:db8::10".parse::<Ipv6Addr>().unwrap(),
+            "2001:db8::11".parse::<Ipv6Addr>().unwrap(),
+        ]
+    );
+    assert_eq!(
+        server.fixed_prefix6,
+        Some(("2001:db8:1::".parse::<Ipv6Addr>().unwrap(), 48))
+    );
+
+    let relayed = hosts[2].host_identifier.as_ref().unwrap();
+    assert_eq!(relayed.v6relopt, Some(1));
+    assert_eq!(relayed.option.value, OptionValue::Data(b"port1".to_vec()));
+}
+
+#[test]
+fn host_scope_paths() {
+    let res = parser::parse(
+        r#"
+shared-network "office" {
+  subnet 10.11.4.0 netmask 255.255.255.0 {
+  }
+  subnet 10.11.5.0 netmask 255.255.255.0 {
+    group {
+      host dual-subnet {
+        hardware ethernet 88:32:9b:07:0a:46;
+        fixed-address 10.11.4.223, 10.11.5.223;
+      }
+    }
+    pool {
+      range 10.11.5.100 10.11.5.200;
+    }
+    host evgenii-hp-probook {
+      hardware ethernet 4c:d5:77:88:cc:3b;
+      fixed-address 10.11.5.222;
+    }
+  }
+}
+
+host top-level-infra {
+  hardware ethernet 30:de:4b:03:a9:89;
+  fixed-address 10.11.4.1;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let hosts = res.unwrap().hosts;
+    assert_eq!(hosts.len(), 3);
+
+    let dual = &hosts[0];
+    assert_eq!(
+        dual.scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24 > group"
+    );
+    assert_eq!(dual.scope.shared_network(), Some("office"));
+    assert_eq!(
+        dual.fixed_address_on_subnet(),
+        Some("10.11.5.223".parse().unwrap())
+    );
+
+    let probook = &hosts[1];
+    assert_eq!(probook.name, "evgenii-hp-probook");
+    assert_eq!(
+        probook.scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24"
+    );
+
+    let infra = &hosts[2];
+    assert!(infra.scope.entries.is_empty());
+    assert_eq!(infra.scope.to_string(), "global");
+    assert_eq!(infra.fixed_address_on_subnet(), None);
+}
@@ -0,0 +1,155 @@
+extern crate dhcpd_parser;
+
+use std::fs;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::include::FsLoader;
+use crate::dhcpd_parser::include::MemoryLoader;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn include_memory_loader_test() {
+    let mut loader = MemoryLoader::new();
+    loader.insert(
+        "dhcpd.conf",
+        r#"
+option domain-name "example.org";
+shared-network office {
+  include "vlan10.conf";
+  include "vlan11.conf";
+}
+"#,
+    );
+    loader.insert(
+        "vlan10.conf",
+        r#"
+subnet 10.0.10.0 netmask 255.255.255.0 {
+  include "hosts/vlan10.conf";
+}
+"#,
+    );
+    loader.insert(
+        "hosts/vlan10.conf",
+        r#"
+host printer {
+  hardware ethernet 00:11:22:33:44:55;
+  fixed-address 10.0.10.5;
+}
+"#,
+    );
+    loader.insert(
+        "vlan11.conf",
+        "subnet 10.0.11.0 netmask 255.255.255.0 { }\n",
+    );
+
+    let res = parser::parse_config_file("dhcpd.conf", &loader);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let shared = config.shared_networks()[0];
+    let subnets = shared.subnets();
+    assert_eq!(subnets.len(), 2);
+    assert_eq!(subnets[0].cidr(), "10.0.10.0/24");
+    assert_eq!(subnets[1].cidr(), "10.0.11.0/24");
+
+    let hosts = subnets[0].hosts();
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].name, "printer");
+
+    match &shared.statements[0].kind {
+        StatementKind::Include(i) => {
+            assert_eq!(i.path, "vlan10.conf");
+            let statements = i.statements.as_ref().unwrap();
+            assert_eq!(statements[0].span.file.as_deref(), Some("vlan10.conf"));
+            assert_eq!(statements[0].span.line, 2);
+            let host = &statements[0].children()[0].children()[0];
+            assert_eq!(host.span.to_string(), "hosts/vlan10.conf, line 2, column 1");
+        }
+        other => panic!("Expected include, got {:?}", other),
+    }
+    assert_eq!(
+        config.statements[0].span.to_string(),
+        "dhcpd.conf, line 2, column 1"
+    );
+
+    let result = parser::parse_file("dhcpd.conf", &loader).unwrap();
+    assert_eq!(result.hosts.len(), 1);
+}
+
+#[test]
+fn include_errors_test() {
+    let mut loader = MemoryLoader::new();
+    loader.insert("a.conf", "include \"b.conf\";\n");
+    loader.insert("b.conf", "\ninclude \"a.conf\";\n");
+    assert_eq!(
+        parser::parse_config_file("a.conf", &loader).err(),
+        Some("b.conf, line 2, column 17: Include cycle: a.conf -> b.conf -> a.conf".to_owned())
+    );
+
+    loader.insert("f.conf", "include \"./g/../f.conf\";\n");
+    assert_eq!(
+        parser::parse_config_file("f.conf", &loader).err(),
+        Some("f.conf, line 1, column 24: Include cycle: f.conf -> f.conf".to_owned())
+    );
+
+    loader.insert("c.conf", "include \"d.conf\";\n");
+    loader.insert(
+        "d.conf",
+        "subnet 10.0.0.0 netmask 255.255.255.0 {\n  range 10.0.0.1 10.0.0;\n}\n",
+    );
+    assert_eq!(
+        parser::parse_config_file("c.conf", &loader).err(),
+        Some("d.conf, line 2, column 18: Invalid range end \"10.0.0\"".to_owned())
+    );
+
+    loader.insert("e.conf", "include \"missing.conf\";\n");
+    assert_eq!(
+        parser::parse_config_file("e.conf", &loader).err(),
+        Some("e.conf, line 1, column 23: No such file \"missing.conf\"".to_owned())
+    );
+
+    let res = parser::parse_config("include \"a.conf\";\n");
+    match &res.unwrap().statements[0].kind {
+        StatementKind::Include(i) => assert!(i.statements.is_none()),
+        other => panic!("Expected include, got {:?}", other),
+    }
+}
+
+#[test]
+fn include_fs_loader_test() {
+    let dir = std::env::temp_dir().join(format!("dhcpd-parser-include-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("dhcpd.conf"), "include \"hosts.conf\";\n").unwrap();
+    fs::write(
+        dir.join("hosts.conf"),
+        "host a { hardware ethernet 00:00:00:00:00:01; }\n",
+    )
+    .unwrap();
+
+    let res = parser::parse_file("dhcpd.conf", &FsLoader::with_root(&dir));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(res.unwrap().hosts[0].name, "a");
+}
+
+#[test]
+fn include_fs_loader_cycle_test() {
+    let dir = std::env::temp_dir().join(format!("dhcpd-parser-cycle-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("dhcpd.conf"), "include \"./dhcpd.conf\";\n").unwrap();
+
+    let res = parser::parse_file("dhcpd.conf", &FsLoader::with_root(&dir));
+    let canonical = fs::canonicalize(dir.join("dhcpd.conf")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        res.err(),
+        Some(format!(
+            "dhcpd.conf, line 1, column 23: Include cycle: {} -> {}",
+            canonical.display(),
+            canonical.display()
+        ))
+    );
+}
@@ -0,0 +1,132 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv6Addr;
+
+use crate::dhcpd_parser::duid::Duid;
+use crate::dhcpd_parser::leases::BindingState;
+use crate::dhcpd_parser::leases6::IaKind;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn ia_na_test() {
+    let res = parser::parse(
+        r#"
+authoring-byte-order little-endian;
+
+ia-na "\001\000\000\000\000\001\000\001\036\225\203\371RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+  iaaddr 2001:db8::10 {
+    binding state active;
+    preferred-life 375;
+    max-life 600;
+    ends 4 2019/03/14 14:30:02;
+  }
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let ias = res.unwrap().ias;
+    assert_eq!(ias.len(), 1);
+
+    let ia = &ias[0];
+    assert_eq!(ia.kind, IaKind::Na);
+    assert_eq!(ia.iaid, 1);
+    assert_eq!(
+        ia.duid,
+        Duid::Llt {
+            hardware_type: 1,
+            time: 0x1e95_83f9,
+            link_layer_address: vec![b'R', b'T', 0, 0x12, 0x34, b'V'],
+        }
+    );
+    assert_eq!(ia.cltt.unwrap().to_string(), "Thursday 2019/03/14 14:20:02");
+
+    assert_eq!(ia.addresses.len(), 1);
+    let addr = &ia.addresses[0];
+    assert_eq!(addr.address, "2001:db8::10".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(addr.binding_state, BindingState::Active);
+    assert_eq!(addr.preferred_life, Some(375));
+    assert_eq!(addr.max_life, Some(600));
+    assert!(addr.ends.is_some());
+}
+
+#[test]
+fn ia_pd_and_ta_test() {
+    let res = parser::parse(
+        r#"
+authoring-byte-order big-endian;
+
+ia-pd "\000\000\000\002\000\003\000\001RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+  iaprefix 2001:db8:100::/56 {
+    binding state expired;
+    preferred-life 375;
+    max-life 600;
+    ends 4 2019/03/14 14:30:02;
+    set client-name = "foo";
+  }
+}
+
+ia-ta "\003\000\000\000\000\003\000\001RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let ias = res.unwrap().ias;
+    assert_eq!(ias.len(), 2);
+
+    assert_eq!(ias[0].kind, IaKind::Pd);
+    assert_eq!(ias[0].iaid, 2);
+    assert_eq!(ias[0].prefixes.len(), 1);
+    let prefix = &ias[0].prefixes[0];
+    assert_eq!(prefix.prefix, "2001:db8:100::".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(prefix.prefix_len, 56);
+    assert_eq!(prefix.binding_state, BindingState::Expired);
+
+    assert_eq!(ias[1].kind, IaKind::Ta);
+    assert_eq!(ias[1].iaid, 0x0300_0000);
+    assert!(ias[1].addresses.is_empty());
+}
+
+#[test]
+fn invalid_iaaddr_test() {
+    let res = parser::parse(
+        r#"
+ia-na "\001\000\000\000\000\001" {
+  iaaddr 10.0.0.1 {
+    binding state active;
+  }
+}
+"#,
+    );
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn escaped_duid_test() {
+    // dhcpd writes ' $ and ` with a backslash in front of them.
+    let res = parser::parse(
+        r#"
+authoring-byte-order little-endian;
+
+ia-na "\001\000\000\000\000\003\000\001\000\021\'3DU" {
+  cltt 4 2019/03/14 14:20:02;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let ia = &res.unwrap().ias[0];
+    assert_eq!(ia.iaid, 1);
+    assert_eq!(
+        ia.duid,
+        Duid::Ll {
+            hardware_type: 1,
+            link_layer_address: vec![0, 0x11, b'\'', b'3', b'D', b'U'],
+        }
+    );
+}
@@ -0,0 +1,199 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::options::OptionRegistry;
+use crate::dhcpd_parser::options::OptionValue;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::ParserOptions;
+
+#[test]
+fn typed_options_test() {
+    let res = parser::parse_config(
+        r#"
+option domain-name-servers 10.0.0.2, ns2.example.org;
+option domain-search "example.org", "corp.example.org";
+option ip-forwarding off;
+option time-offset -3600;
+option dhcp-client-identifier 1:0:c:29:ab:cd:ef;
+option static-routes 10.1.0.0 10.0.0.1, 10.2.0.0 10.0.0.1;
+option dhcp6.name-servers 2001:db8::53;
+option local-proxy-config "http://proxy";
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    assert_eq!(config.options().len(), 8);
+
+    let dns = config.option("domain-name-servers").unwrap();
+    assert_eq!(dns.code, Some(6));
+    assert_eq!(
+        dns.value,
+        OptionValue::Array(vec![
+            OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 2)),
+            OptionValue::Hostname("ns2.example.org".to_owned()),
+        ])
+    );
+    assert_eq!(
+        config.option("domain-search").unwrap().value,
+        OptionValue::DomainList(vec![
+            "example.org".to_owned(),
+            "corp.example.org".to_owned()
+        ])
+    );
+    assert_eq!(
+        config.option("ip-forwarding").unwrap().value,
+        OptionValue::Boolean(false)
+    );
+    assert_eq!(
+        config.option("time-offset").unwrap().value,
+        OptionValue::Signed(-3600)
+    );
+    assert_eq!(
+        config.option("dhcp-client-identifier").unwrap().value,
+        OptionValue::Data(vec![1, 0, 0xc, 0x29, 0xab, 0xcd, 0xef])
+    );
+    assert_eq!(
+        config.option("static-routes").unwrap().value,
+        OptionValue::Array(vec![
+            OptionValue::Record(vec![
+                OptionValue::Ip(Ipv4Addr::new(10, 1, 0, 0)),
+                OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 1)),
+            ]),
+            OptionValue::Record(vec![
+                OptionValue::Ip(Ipv4Addr::new(10, 2, 0, 0)),
+                OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 1)),
+            ]),
+        ])
+    );
+
+    let ns6 = config.option("dhcp6.name-servers").unwrap();
+    assert_eq!(ns6.space, "dhcp6");
+    assert_eq!(ns6.code, Some(23));
+    assert_eq!(
+        ns6.value,
+        OptionValue::Array(vec![OptionValue::Ip6(
+            "2001:db8::53".parse::<Ipv6Addr>().unwrap()
+        )])
+    );
+    assert_eq!(ns6.to_string(), "option dhcp6.name-servers 2001:db8::53;");
+
+    let unknown = config.option("local-proxy-config").unwrap();
+    assert_eq!(unknown.code, None);
+    assert_eq!(
+        unknown.value,
+        OptionValue::Unknown(vec!["\"http://proxy\"".to_owned()])
+    );
+
+    assert_eq!(OptionRegistry::builtin().lookup("routers").unwrap().code, 3);
+}
+
+#[test]
+fn invalid_option_value_test() {
+    let input = r#"
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  option interface-mtu 70000;
+  option domain-name-servers 10.0.0.1 10.0.0.2;
+}
+"#;
+
+    let res = parser::parse_config(input);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let subnet = config.subnets()[0];
+    assert_eq!(
+        subnet.option("interface-mtu").unwrap().value,
+        OptionValue::Unknown(vec!["70000".to_owned()])
+    );
+    let servers = subnet.option("domain-name-servers").unwrap();
+    assert_eq!(servers.code, Some(6));
+    assert_eq!(
+        servers.value,
+        OptionValue::Unknown(vec!["10.0.0.1".to_owned(), "10.0.0.2".to_owned()])
+    );
+
+    let res = parser::parse_config_with(input, &ParserOptions::new().strict_options(true));
+    assert_eq!(
+        res.err(),
+        Some(
+            "line 3, column 24: Option interface-mtu: \"70000\" is not a valid unsigned integer 16"
+                .to_owned()
+        )
+    );
+
+    let res = parser::parse_config_with(
+        "option domain-name-servers 10.0.0.1 10.0.0.2;\n",
+        &ParserOptions::new().strict(true),
+    );
+    assert_eq!(
+        res.err(),
+        Some(
+            "line 1, column 37: Expected ';' after option domain-name-servers, got Some(Word(\"10.0.0.2\"))"
+                .to_owned()
+        )
+    );
+}
+
+#[test]
+fn option_definitions_test() {
+    let res = parser::parse_config(
+        r#"
+option space pxelinux code width 1 length width 1;
+option pxelinux.magic code 208 = string;
+option pxelinux.reboottime code 211 = unsigned integer 32;
+option voip-tftp code 150 = array of ip-address;
+option sip-route code 240 = { ip-address, unsigned integer 16, text };
+
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  option voip-tftp 10.0.0.5, 10.0.0.6;
+  option pxelinux.magic f1:00:74:7e;
+  option pxelinux.reboottime 30;
+  option sip-route 10.0.0.7 5060 "udp";
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let subnet = config.subnets()[0];
+
+    let voip = subnet.option("voip-tftp").unwrap();
+    assert_eq!(voip.code, Some(150));
+    assert_eq!(
+        voip.value,
+        OptionValue::Array(vec![
+            OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 5)),
+            OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 6)),
+        ])
+    );
+
+    let magic = subnet.option("pxelinux.magic").unwrap();
+    assert_eq!(magic.space, "pxelinux");
+    assert_eq!(magic.code, Some(208));
+    assert_eq!(magic.value, OptionValue::Data(vec![0xf1, 0, 0x74, 0x7e]));
+    assert_eq!(
+        subnet.option("pxelinux.reboottime").unwrap().value,
+        OptionValue::Unsigned(30)
+    );
+    assert_eq!(
+        subnet.option("sip-route").unwrap().value,
+        OptionValue::Record(vec![
+            OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 7)),
+            OptionValue::Unsigned(5060),
+            OptionValue::Text("udp".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn option_definition_unknown_space_test() {
+    let res = parser::parse_config("option ipxe.no-pxedhcp code 176 = unsigned integer 8;\n");
+
+    assert_eq!(
+        res.err(),
+        Some("line 1, column 53: Unknown option space \"ipxe\"".to_owned())
+    );
+}
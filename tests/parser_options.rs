@@ -0,0 +1,143 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::include::MemoryLoader;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::LeasesMethods;
+use crate::dhcpd_parser::parser::ParserOptions;
+
+#[test]
+fn strict_statements_test() {
+    let input = r#"
+ddns-updates on;
+unknown-block foo {
+  anything goes;
+}
+"#;
+
+    let res = parser::parse_config(input);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(res.unwrap().statements.len(), 2);
+
+    let options = ParserOptions::new().strict_statements(true);
+    let res = parser::parse_config_with(input, &options);
+    assert_eq!(
+        res.err(),
+        Some("line 3, column 1: Unknown declaration \"unknown-block\"".to_owned())
+    );
+
+    let res = parser::parse_config_with("option routers 10.0.0.1;\n}\n", &options);
+    assert_eq!(
+        res.err(),
+        Some("line 2, column 1: Unexpected '}'".to_owned())
+    );
+}
+
+#[test]
+fn lenient_leases_test() {
+    let input = r#"
+lease 10.0.0.5 {
+  starts 2 2019/01/01 22:00:00 UTC;
+  option agent.circuit-id "port1";
+  hardware ethernet 11:22:33:44:55:66;
+}
+"#;
+
+    let res = parser::parse(input);
+    assert_eq!(
+        res.err(),
+        Some("line 3, column 35: Unexpected option 'option'".to_owned())
+    );
+
+    let options = ParserOptions::new().strict_leases(false);
+    let res = parser::parse_with(input, &options);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let leases = res.unwrap().leases;
+    assert_eq!(leases.all().len(), 1);
+    assert_eq!(
+        leases[0].hardware.as_ref().unwrap().mac,
+        "11:22:33:44:55:66"
+    );
+    assert_eq!(leases[0].unknown.len(), 1);
+    assert_eq!(leases[0].unknown[0].keyword, "option");
+    assert_eq!(
+        leases[0].unknown[0].tokens,
+        vec!["agent.circuit-id", "\"port1\""]
+    );
+}
+
+#[test]
+fn max_depth_test() {
+    let nested = format!("{}{}", "group {\n".repeat(5), "}\n".repeat(5));
+    let options = ParserOptions::new().max_depth(5);
+    let res = parser::parse_config_with(nested.as_str(), &options);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+
+    let options = ParserOptions::new().max_depth(4);
+    let res = parser::parse_config_with(nested.as_str(), &options);
+    assert_eq!(
+        res.err(),
+        Some("line 4, column 7: Nesting deeper than 4 levels".to_owned())
+    );
+
+    // The default limit keeps deeply nested input from overflowing the stack.
+    let nested = "group {\n".repeat(100_000);
+    let res = parser::parse_config(nested.as_str());
+    assert_eq!(
+        res.err(),
+        Some("line 32, column 7: Nesting deeper than 32 levels".to_owned())
+    );
+
+    let expression = format!("if {}true{} {{ }}\n", "(".repeat(1000), ")".repeat(1000));
+    let res = parser::parse_config(expression.as_str());
+    assert_eq!(
+        res.err(),
+        Some("line 1, column 67: Expression nested deeper than 64 levels".to_owned())
+    );
+}
+
+#[test]
+fn max_depth_include_test() {
+    let mut loader = MemoryLoader::new();
+    loader.insert("dhcpd.conf", "group {\n  include \"hosts.conf\";\n}\n");
+    loader.insert("hosts.conf", "host a {\n  fixed-address 10.0.0.1;\n}\n");
+
+    let options = ParserOptions::new().max_depth(3);
+    let res = parser::parse_file_with("dhcpd.conf", &loader, &options);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+
+    let options = ParserOptions::new().max_depth(2);
+    let res = parser::parse_file_with("dhcpd.conf", &loader, &options);
+    assert_eq!(
+        res.err(),
+        Some("hosts.conf, line 1, column 1: Nesting deeper than 2 levels".to_owned())
+    );
+}
+
+#[test]
+fn max_token_length_test() {
+    let input = format!("option domain-name \"{}\";\n", "a".repeat(100));
+    let options = ParserOptions::new().max_token_length(64);
+    let res = parser::parse_config_with(input.as_str(), &options);
+    assert_eq!(
+        res.err(),
+        Some("line 1, column 20: Token longer than 64 bytes".to_owned())
+    );
+
+    let options = ParserOptions::new().max_token_length(102);
+    assert!(parser::parse_config_with(input.as_str(), &options).is_ok());
+}
+
+#[test]
+fn max_declarations_test() {
+    let input = "subnet 10.0.0.0 netmask 255.255.255.0 {\n  range 10.0.0.10 10.0.0.20;\n}\n";
+    let options = ParserOptions::new().max_declarations(2);
+    let res = parser::parse_config_with(input, &options);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+
+    let options = ParserOptions::new().max_declarations(1);
+    let res = parser::parse_config_with(input, &options);
+    assert_eq!(
+        res.err(),
+        Some("line 3, column 1: More than 1 declarations".to_owned())
+    );
+}
@@ -0,0 +1,164 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv4Addr;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::options::OptionValue;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::resolve;
+
+const CONFIG: &str = r#"
+default-lease-time 600;
+max-lease-time 7200;
+option domain-name-servers 10.0.0.53;
+option routers 10.0.0.1;
+
+shared-network "office" {
+  default-lease-time 1200;
+  subnet 10.11.5.0 netmask 255.255.255.0 {
+    option routers 10.11.5.1;
+    pool {
+      max-lease-time 3600;
+      range 10.11.5.100 10.11.5.200;
+    }
+    group {
+      filename "pxelinux.0";
+      host printer {
+        hardware ethernet 00:11:22:33:44:55;
+        fixed-address 10.11.5.10;
+        default-lease-time 86400;
+      }
+      host phone {
+        hardware ethernet 00:11:22:33:44:66;
+        fixed-address 10.11.5.11;
+        filename "phone.cfg";
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn resolve_host_test() {
+    let config = parser::parse_config(CONFIG).unwrap();
+    let host = config.shared_networks()[0].subnets()[0].hosts()[0];
+
+    let effective = resolve::resolve_host(&config, host).unwrap();
+    assert_eq!(effective.default_lease_time(), Some(86400));
+    let default_lease_time = effective.parameter("default-lease-time").unwrap();
+    assert_eq!(
+        default_lease_time.scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24 > group > host printer"
+    );
+    assert_eq!(effective.max_lease_time(), Some(7200));
+    assert_eq!(
+        effective
+            .parameter("max-lease-time")
+            .unwrap()
+            .scope
+            .to_string(),
+        "global"
+    );
+    assert_eq!(effective.filename().as_deref(), Some("pxelinux.0"));
+
+    let routers = effective.option("routers").unwrap();
+    assert_eq!(
+        routers.value.value,
+        OptionValue::Array(vec![OptionValue::Ip(Ipv4Addr::new(10, 11, 5, 1))])
+    );
+    assert_eq!(
+        routers.scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24"
+    );
+    assert_eq!(
+        effective
+            .option("domain-name-servers")
+            .unwrap()
+            .scope
+            .to_string(),
+        "global"
+    );
+
+    let phone = resolve::resolve_mac(&config, "00:11:22:33:44:66").unwrap();
+    assert_eq!(phone.filename().as_deref(), Some("phone.cfg"));
+    assert_eq!(
+        phone.parameter("filename").unwrap().scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24 > group > host phone"
+    );
+    assert_eq!(phone.default_lease_time(), Some(1200));
+
+    assert!(resolve::resolve_mac(&config, "00:00:00:00:00:00").is_none());
+}
+
+#[test]
+fn resolve_address_test() {
+    let config = parser::parse_config(CONFIG).unwrap();
+
+    let host = resolve::resolve_address(&config, Ipv4Addr::new(10, 11, 5, 10)).unwrap();
+    assert_eq!(host.default_lease_time(), Some(86400));
+
+    let pool = resolve::resolve_address(&config, Ipv4Addr::new(10, 11, 5, 150)).unwrap();
+    assert_eq!(pool.max_lease_time(), Some(3600));
+    assert_eq!(
+        pool.parameter("max-lease-time").unwrap().scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24 > pool"
+    );
+    assert_eq!(pool.default_lease_time(), Some(1200));
+    assert_eq!(pool.filename(), None);
+
+    let subnet = resolve::resolve_address(&config, Ipv4Addr::new(10, 11, 5, 20)).unwrap();
+    assert_eq!(subnet.max_lease_time(), Some(7200));
+    assert_eq!(
+        subnet.option("routers").unwrap().scope.to_string(),
+        "shared-network \"office\" > subnet 10.11.5.0/24"
+    );
+
+    assert!(resolve::resolve_address(&config, Ipv4Addr::new(192, 168, 0, 1)).is_none());
+}
+
+#[test]
+fn resolve_top_level_host_test() {
+    let config = parser::parse_config(
+        r#"
+default-lease-time 600;
+
+shared-network "office" {
+  default-lease-time 1200;
+  subnet 10.0.0.0 netmask 255.255.255.0 {
+    option routers 10.0.0.1;
+  }
+}
+
+group {
+  filename "pxelinux.0";
+  host a {
+    hardware ethernet 00:11:22:33:44:55;
+    fixed-address 10.0.0.5;
+  }
+}
+
+host b {
+  hardware ethernet 00:11:22:33:44:66;
+  fixed-address 192.168.0.5;
+}
+"#,
+    )
+    .unwrap();
+
+    let a = resolve::resolve_mac(&config, "00:11:22:33:44:55").unwrap();
+    let routers = a.option("routers").unwrap();
+    assert_eq!(
+        routers.value.value,
+        OptionValue::Array(vec![OptionValue::Ip(Ipv4Addr::new(10, 0, 0, 1))])
+    );
+    assert_eq!(
+        routers.scope.to_string(),
+        "shared-network \"office\" > subnet 10.0.0.0/24"
+    );
+    assert_eq!(a.default_lease_time(), Some(1200));
+    assert_eq!(a.parameter("filename").unwrap().scope.to_string(), "group");
+
+    let b = resolve::resolve_mac(&config, "00:11:22:33:44:66").unwrap();
+    assert!(b.option("routers").is_none());
+    assert_eq!(b.default_lease_time(), Some(600));
+}
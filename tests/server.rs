@@ -0,0 +1,113 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::include::MemoryLoader;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::server::DdnsUpdateStyle;
+
+#[test]
+fn server_parameters_test() {
+    let res = parser::parse_config(
+        r#"
+authoritative;
+default-lease-time 600;
+max-lease-time 7200;
+min-lease-time 300;
+ddns-update-style interim;
+log-facility local7;
+ping-check true;
+one-lease-per-client on;
+use-host-decl-names off;
+server-identifier 10.11.4.1;
+next-server tftp.example.org;
+filename "pxelinux.0";
+local-address 10.11.4.1;
+
+subnet 10.11.5.0 netmask 255.255.255.0 {
+  default-lease-time 60;
+  not authoritative;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let params = &config.parameters;
+
+    assert_eq!(params.authoritative, Some(true));
+    assert_eq!(params.default_lease_time, Some(600));
+    assert_eq!(params.max_lease_time, Some(7200));
+    assert_eq!(params.min_lease_time, Some(300));
+    assert_eq!(params.ddns_update_style, Some(DdnsUpdateStyle::Interim));
+    assert_eq!(params.log_facility.as_deref(), Some("local7"));
+    assert_eq!(params.ping_check, Some(true));
+    assert_eq!(params.one_lease_per_client, Some(true));
+    assert_eq!(params.use_host_decl_names, Some(false));
+    assert_eq!(params.server_identifier.as_deref(), Some("10.11.4.1"));
+    assert_eq!(params.next_server.as_deref(), Some("tftp.example.org"));
+    assert_eq!(params.filename.as_deref(), Some("pxelinux.0"));
+
+    match &config.statements[12].kind {
+        StatementKind::Parameter(p) => {
+            assert_eq!(p.name, "local-address");
+            assert_eq!(p.args, vec!["10.11.4.1"]);
+        }
+        other => panic!("Expected parameter, got {:?}", other),
+    }
+}
+
+#[test]
+fn not_authoritative_test() {
+    let config = parser::parse_config("not authoritative;\nping-check false;\n").unwrap();
+
+    assert_eq!(config.parameters.authoritative, Some(false));
+    assert_eq!(config.parameters.ping_check, Some(false));
+    assert_eq!(config.parameters.default_lease_time, None);
+}
+
+#[test]
+fn invalid_server_parameter_test() {
+    let res = parser::parse_config("default-lease-time 600;\nmax-lease-time forever;\n");
+    assert_eq!(
+        res.err(),
+        Some("line 2, column 23: Invalid max-lease-time \"forever\"".to_owned())
+    );
+
+    let res = parser::parse_config("ddns-update-style fancy;\n");
+    assert_eq!(
+        res.err(),
+        Some("line 1, column 24: 'fancy' is not a recognized ddns-update-style".to_owned())
+    );
+}
+
+#[test]
+fn nested_parameters_test() {
+    let res = parser::parse_config(
+        r#"
+default-lease-time 600;
+max-lease-time 7200;
+class "x" {
+  default-lease-time 60;
+}
+if option vendor-class-identifier = "PXEClient" {
+  max-lease-time 10;
+}
+host a {
+  filename "a.cfg";
+}
+"#,
+    );
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    assert_eq!(config.parameters.default_lease_time, Some(600));
+    assert_eq!(config.parameters.max_lease_time, Some(7200));
+    assert_eq!(config.parameters.filename, None);
+
+    // Included files at the global level are part of the global scope.
+    let mut loader = MemoryLoader::new();
+    loader.insert("dhcpd.conf", "include \"global.conf\";\n");
+    loader.insert("global.conf", "default-lease-time 900;\n");
+    let config = parser::parse_config_file("dhcpd.conf", &loader).unwrap();
+    assert_eq!(config.parameters.default_lease_time, Some(900));
+}
@@ -0,0 +1,109 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv4Addr;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::options::OptionValue;
+use crate::dhcpd_parser::parser;
+
+#[test]
+fn shared_network_subnets_test() {
+    let res = parser::parse_config(
+        r#"
+option routers 10.11.4.1;
+
+shared-network office {
+  option domain-name "office.example.org";
+
+  subnet 10.11.5.0 netmask 255.255.255.0 {
+    range 10.11.5.50 10.11.5.99;
+    range dynamic-bootp 10.11.5.200 10.11.5.210;
+    option routers 10.11.5.1;
+    option domain-name-servers 10.11.5.2, 10.11.5.3;
+    pool {
+      range 10.11.5.100 10.11.5.150;
+    }
+    group {
+      host printer {
+        hardware ethernet 00:11:22:33:44:55;
+        fixed-address 10.11.5.20;
+      }
+    }
+    host laptop {
+      hardware ethernet 00:11:22:33:44:66;
+    }
+  }
+
+  subnet 10.11.6.0 netmask 255.255.254.0 {
+  }
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    assert_eq!(
+        config.option("routers").unwrap().value,
+        OptionValue::Array(vec![OptionValue::Ip(Ipv4Addr::new(10, 11, 4, 1))])
+    );
+    assert!(config.subnets().is_empty());
+
+    let shared = config.shared_networks();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].options().len(), 1);
+
+    let subnets = shared[0].subnets();
+    assert_eq!(subnets.len(), 2);
+    assert_eq!(subnets[0].cidr(), "10.11.5.0/24");
+    assert_eq!(subnets[1].cidr(), "10.11.6.0/23");
+    assert!(subnets[1].contains(Ipv4Addr::new(10, 11, 7, 200)));
+    assert!(!subnets[1].contains(Ipv4Addr::new(10, 11, 8, 1)));
+
+    let subnet = subnets[0];
+    let ranges = subnet.ranges();
+    assert_eq!(ranges.len(), 2);
+    assert!(!ranges[0].dynamic_bootp);
+    assert!(ranges[1].dynamic_bootp);
+    assert!(ranges[0].contains(Ipv4Addr::new(10, 11, 5, 60)));
+    assert!(!ranges[0].contains(Ipv4Addr::new(10, 11, 5, 100)));
+
+    let pools = subnet.pools();
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].ranges()[0].low, Ipv4Addr::new(10, 11, 5, 100));
+
+    let hosts = subnet.hosts();
+    assert_eq!(hosts.len(), 2);
+    assert_eq!(hosts[0].name, "printer");
+    assert_eq!(hosts[1].name, "laptop");
+
+    assert_eq!(subnet.options().len(), 2);
+    assert_eq!(
+        subnet.option("routers").unwrap().value,
+        OptionValue::Array(vec![OptionValue::Ip(Ipv4Addr::new(10, 11, 5, 1))])
+    );
+}
+
+#[test]
+fn invalid_netmask_test() {
+    let res = parser::parse_config("subnet 10.11.5.0 netmask 255.0.255.0 { }");
+    assert!(res.is_err());
+
+    let res = parser::parse_config("subnet 10.11.5.1 netmask 255.255.255.0 { }");
+    assert!(res.is_err());
+}
+
+#[test]
+fn invalid_range_test() {
+    let res = parser::parse_config(
+        r#"
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  range 10.0.0.200 10.0.0.10;
+}
+"#,
+    );
+
+    assert_eq!(
+        res.err(),
+        Some("line 3, column 29: Range start 10.0.0.200 is above its end 10.0.0.10".to_owned())
+    );
+}
@@ -0,0 +1,100 @@
+extern crate dhcpd_parser;
+
+use std::net::Ipv6Addr;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::resolve::resolve_address6;
+
+fn ip6(s: &str) -> Ipv6Addr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn subnet6_test() {
+    let res = parser::parse_config(
+        r#"
+default-lease-time 2592000;
+option dhcp6.name-servers 2001:db8::53;
+
+subnet6 2001:db8:1::/64 {
+  range6 2001:db8:1::100 2001:db8:1::200;
+  range6 2001:db8:1:0:8000::/65 temporary;
+  prefix6 2001:db8:1:100:: 2001:db8:1:f00:: /56;
+  option dhcp6.domain-search "v6.example.org";
+
+  host server6 {
+    host-identifier option dhcp6.client-id 0:1:0:1:1c:39:cf:88:8:0:27:fe:8f:95;
+    fixed-address6 2001:db8:1::10;
+  }
+}
+
+subnet6 2001:db8:2::/64 {
+  range6 2001:db8:2::1;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+
+    let subnets = config.subnets6();
+    assert_eq!(subnets.len(), 2);
+    assert_eq!(subnets[0].cidr(), "2001:db8:1::/64");
+    assert!(subnets[0].contains(ip6("2001:db8:1::ffff")));
+    assert!(!subnets[0].contains(ip6("2001:db8:2::1")));
+
+    let ranges = subnets[0].ranges6();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0].low, ip6("2001:db8:1::100"));
+    assert_eq!(ranges[0].high, ip6("2001:db8:1::200"));
+    assert!(!ranges[0].temporary);
+    assert_eq!(ranges[1].low, ip6("2001:db8:1:0:8000::"));
+    assert_eq!(ranges[1].high, ip6("2001:db8:1:0:ffff:ffff:ffff:ffff"));
+    assert!(ranges[1].temporary);
+    assert_eq!(subnets[1].ranges6()[0].high, ip6("2001:db8:2::1"));
+
+    let prefixes = subnets[0].prefixes6();
+    assert_eq!(prefixes.len(), 1);
+    assert_eq!(prefixes[0].prefix_len, 56);
+    assert!(prefixes[0].contains((ip6("2001:db8:1:200::"), 56)));
+    assert!(!prefixes[0].contains((ip6("2001:db8:1:200::"), 64)));
+
+    let host = subnets[0].hosts()[0];
+    assert_eq!(host.scope.subnet6(), Some((ip6("2001:db8:1::"), 64)));
+    assert_eq!(host.fixed_address6_on_subnet(), Some(ip6("2001:db8:1::10")));
+
+    let effective = resolve_address6(&config, ip6("2001:db8:1::150")).unwrap();
+    assert_eq!(effective.default_lease_time(), Some(2592000));
+    let search = effective.option("dhcp6.domain-search").unwrap();
+    assert_eq!(search.scope.to_string(), "subnet6 2001:db8:1::/64");
+    assert!(resolve_address6(&config, ip6("2001:db8:3::1")).is_none());
+}
+
+#[test]
+fn invalid_range6_test() {
+    let res = parser::parse_config(
+        r#"
+subnet6 2001:db8:1::/64 {
+  range6 2001:db8:1::200 2001:db8:1::100;
+}
+"#,
+    );
+
+    assert_eq!(
+        res.err(),
+        Some(
+            "line 3, column 41: Range6 start 2001:db8:1::200 is above its end 2001:db8:1::100"
+                .to_owned()
+        )
+    );
+
+    let res = parser::parse_config("subnet6 2001:db8:1::1/64 {\n}\n");
+    assert_eq!(
+        res.err(),
+        Some(
+            "line 1, column 9: Subnet6 prefix 2001:db8:1::1/64 has bits set outside the prefix length"
+                .to_owned()
+        )
+    );
+}
@@ -0,0 +1,153 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::config::StatementKind;
+use crate::dhcpd_parser::config::Unknown;
+use crate::dhcpd_parser::dialect::Dialect;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::parser::ParserOptions;
+use crate::dhcpd_parser::writer;
+
+fn unknown(kind: &StatementKind) -> &Unknown {
+    match kind {
+        StatementKind::Unknown(v) => v,
+        other => panic!("Expected unknown statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_blocks_kept_in_place_test() {
+    let res = parser::parse_config(
+        r#"
+option routers 10.0.0.1;
+
+dhcp-vendor-block "acme" {
+  firmware "v2.bin";
+  nested x {
+    deep 1 2 3;
+  }
+  trailing
+}
+
+subnet 10.0.0.0 netmask 255.255.255.0 {
+  { orphan; }
+  range 10.0.0.10 10.0.0.20;
+}
+"#,
+    );
+
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    assert_eq!(config.statements.len(), 3);
+
+    let statement = &config.statements[1];
+    assert_eq!(statement.span.line, 4);
+    let block = unknown(&statement.kind);
+    assert_eq!(block.keyword, "dhcp-vendor-block");
+    assert_eq!(block.tokens, vec!["\"acme\""]);
+    assert_eq!(block.span, statement.span);
+
+    let children = block.children.as_ref().unwrap();
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[0].keyword, "firmware");
+    assert_eq!(children[0].tokens, vec!["\"v2.bin\""]);
+    assert_eq!(children[0].children, None);
+    assert_eq!(children[1].keyword, "nested");
+    assert_eq!(children[1].tokens, vec!["x"]);
+    let deep = &children[1].children.as_ref().unwrap()[0];
+    assert_eq!(deep.keyword, "deep");
+    assert_eq!(deep.tokens, vec!["1", "2", "3"]);
+    assert_eq!(deep.span.line, 7);
+    assert_eq!(children[2].keyword, "trailing");
+
+    let subnet = config.subnets()[0];
+    assert_eq!(subnet.statements.len(), 2);
+    let orphan = unknown(&subnet.statements[0].kind);
+    assert_eq!(orphan.keyword, "");
+    assert_eq!(orphan.children.as_ref().unwrap()[0].keyword, "orphan");
+    assert_eq!(subnet.ranges().len(), 1);
+}
+
+#[test]
+fn unknown_block_depth_test() {
+    let nested = format!("outer {{\n{}{}}}\n", "a {\n".repeat(10), "}\n".repeat(10));
+
+    let options = ParserOptions::new().max_depth(11);
+    let res = parser::parse_config_with(nested.as_str(), &options);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+
+    let options = ParserOptions::new().max_depth(10);
+    let res = parser::parse_config_with(nested.as_str(), &options);
+    assert_eq!(
+        res.err(),
+        Some("line 10, column 3: Nesting deeper than 10 levels".to_owned())
+    );
+
+    // Nesting is bounded without recursing, and the default limit applies.
+    let nested = "a {\n".repeat(100_000);
+    let res = parser::parse_config(nested.as_str());
+    assert_eq!(
+        res.err(),
+        Some("line 32, column 3: Nesting deeper than 32 levels".to_owned())
+    );
+}
+
+#[test]
+fn unknown_leases6_entries_test() {
+    let input = r#"
+authoring-byte-order little-endian;
+
+failover peer "fo" state {
+  my state normal at 1 2019/01/07 10:11:12;
+  partner state normal;
+  stos 1546855872;
+}
+
+ia-na "\001\000\000\000\000\003\000\001RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+  ia-hint 1;
+  iaaddr 2001:db8::10 {
+    binding state active;
+    preferred-life 375;
+    max-life 600;
+    ends 4 2019/03/14 14:30:02;
+    set ddns-fwd-name = "host.example.org";
+    on expiry {
+      log ("expired");
+    }
+  }
+}
+"#;
+
+    let res = parser::parse(input);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+
+    let peer = &result.failover_peers[0];
+    assert_eq!(peer.unknown.len(), 1);
+    assert_eq!(peer.unknown[0].keyword, "stos");
+    assert_eq!(peer.unknown[0].tokens, vec!["1546855872"]);
+
+    let ia = &result.ias[0];
+    assert_eq!(ia.unknown.len(), 1);
+    assert_eq!(ia.unknown[0].keyword, "ia-hint");
+    let addr = &ia.addresses[0];
+    assert_eq!(addr.unknown.len(), 2);
+    assert_eq!(addr.unknown[0].keyword, "set");
+    assert_eq!(
+        addr.unknown[0].tokens,
+        vec!["ddns-fwd-name", "=", "\"host.example.org\""]
+    );
+    assert_eq!(addr.unknown[1].keyword, "on");
+    let children = addr.unknown[1].children.as_ref().unwrap();
+    assert_eq!(children[0].keyword, "log");
+
+    // Writing the entries back keeps them.
+    let output = writer::write_result(&result, Dialect::Auto).unwrap();
+    assert!(output.contains("    set ddns-fwd-name = \"host.example.org\";\n"));
+    assert!(output.contains("  stos 1546855872;\n"));
+    let written = parser::parse(output.as_str()).unwrap();
+    assert_eq!(written.ias[0].addresses[0].unknown.len(), 2);
+    assert_eq!(written.ias[0].unknown[0].keyword, "ia-hint");
+    assert_eq!(written.failover_peers[0].unknown[0].keyword, "stos");
+}
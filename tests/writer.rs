@@ -0,0 +1,203 @@
+extern crate dhcpd_parser;
+
+use crate::dhcpd_parser::config::Scope;
+use crate::dhcpd_parser::dialect::Dialect;
+use crate::dhcpd_parser::parser;
+use crate::dhcpd_parser::writer;
+
+#[test]
+fn leases_round_trip_test() {
+    let input = r#"
+authoring-byte-order big-endian;
+server-duid "\000\001\000\001\036\225\203\371RT\000\022\064V";
+
+failover peer "dhcp-failover" state {
+  my state communications-interrupted at 1 2019/01/07 10:11:12;
+  partner state normal;
+  mclt 3600;
+}
+
+class "omapi-class" {
+  dynamic;
+  lease limit 10;
+}
+
+subclass "allocation-class" 1:0:c:29:ab:cd:ef {
+  dynamic;
+  lease limit 2;
+}
+
+subclass "vendor-classes" "PXE \"Client\"" {
+  deleted;
+}
+
+lease 10.0.0.5 {
+  starts 1 2019/01/07 10:00:00;
+  ends 1 2019/01/07 12:00:00;
+  cltt 1 2019/01/07 10:00:00;
+  binding state active;
+  next binding state free;
+  hardware ethernet 11:22:33:44:55:66;
+  uid "\001\021\"3DUf";
+  set vendor-class-identifier = "MSFT 5.0";
+  set ddns-fwd-name = "foo.example";
+  set ddns-server-id = 1;
+  client-hostname "laptop \"one\"";
+}
+
+host omapi-client {
+  dynamic;
+  hardware ethernet AA:BB:CC:DD:EE:FF;
+  uid 1:aa:bb:cc:dd:ee:ff;
+  fixed-address 10.11.5.100, 10.11.5.101;
+  option host-name "omapi-client";
+}
+
+ia-pd "\000\000\000\002\000\003\000\001RT\000\022\064V" {
+  cltt 4 2019/03/14 14:20:02;
+  iaprefix 2001:db8:1::/64 {
+    binding state active;
+    preferred-life 375;
+    max-life 600;
+    ends 4 2019/03/14 14:30:02;
+  }
+}
+"#;
+
+    let res = parser::parse(input);
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let result = res.unwrap();
+    assert_eq!(
+        result.leases[0].variables,
+        vec![
+            ("ddns-fwd-name".to_owned(), "\"foo.example\"".to_owned()),
+            ("ddns-server-id".to_owned(), "1".to_owned()),
+        ]
+    );
+    assert!(result.leases[0].unknown.is_empty());
+
+    let output = writer::write_result(&result, Dialect::Auto).unwrap();
+    assert!(output.contains("  set ddns-fwd-name = \"foo.example\";\n"));
+    let res = parser::parse(output.as_str());
+    assert!(res.is_ok(), "parse of {} failed: {:?}", output, res.err());
+    let written = res.unwrap();
+
+    assert_eq!(written.leases, result.leases);
+    assert_eq!(written.ias, result.ias);
+    assert_eq!(written.authoring_byte_order, result.authoring_byte_order);
+    assert_eq!(written.server_duid, result.server_duid);
+    assert_eq!(written.failover_peers, result.failover_peers);
+    assert_eq!(written.classes, result.classes);
+    assert_eq!(written.subclasses, result.subclasses);
+    assert_eq!(written.dialect, Some(Dialect::Isc));
+
+    // Statements are equal apart from their spans.
+    assert_eq!(written.hosts.len(), 1);
+    let (host, written_host) = (&result.hosts[0], &written.hosts[0]);
+    assert_eq!(written_host.name, host.name);
+    assert_eq!(written_host.mac, host.mac);
+    assert_eq!(written_host.uid, host.uid);
+    assert_eq!(written_host.fixed_addresses, host.fixed_addresses);
+    assert_eq!(written_host.statements.len(), 1);
+    assert_eq!(written_host.statements[0].kind, host.statements[0].kind);
+
+    assert_eq!(
+        writer::write_result(&written, Dialect::Auto).unwrap(),
+        output
+    );
+}
+
+#[test]
+fn write_lease_dialects_test() {
+    let res = parser::parse(
+        r#"
+lease 192.168.0.2 {
+  starts 2 2019/01/01 22:00:00 UTC;
+  ends 2 2019/01/01 23:00:00 UTC;
+  hardware ethernet 11:11:11:11:11:11;
+  uid Client1;
+  client-hostname "CLIENTHOSTNAME";
+  hostname "TESTHOSTNAME";
+  abandoned;
+}
+"#,
+    );
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let lease = &res.unwrap().leases[0];
+
+    assert_eq!(
+        writer::write_lease(lease, Dialect::OpenBsd),
+        "lease 192.168.0.2 {
+\tstarts 2 2019/01/01 22:00:00 UTC;
+\tends 2 2019/01/01 23:00:00 UTC;
+\thardware ethernet 11:11:11:11:11:11;
+\tuid \"Client1\";
+\tabandoned;
+\tclient-hostname \"CLIENTHOSTNAME\";
+\thostname \"TESTHOSTNAME\";
+}
+"
+    );
+    assert_eq!(
+        writer::write_lease(lease, Dialect::Isc),
+        "lease 192.168.0.2 {
+  starts 2 2019/01/01 22:00:00;
+  ends 2 2019/01/01 23:00:00;
+  binding state abandoned;
+  hardware ethernet 11:11:11:11:11:11;
+  uid \"Client1\";
+  client-hostname \"CLIENTHOSTNAME\";
+}
+"
+    );
+
+    let output = writer::write_lease(lease, Dialect::OpenBsd);
+    let res = parser::parse(output.as_str());
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    assert_eq!(&res.unwrap().leases[0], lease);
+}
+
+#[test]
+fn write_host_test() {
+    let res = parser::parse_config(
+        r#"
+host "pxe client" {
+  hardware ethernet 00:11:22:33:44:55;
+  fixed-address6 2001:db8::10;
+  filename "pxelinux.0";
+  next-server 10.0.0.1;
+  if option vendor-class-identifier = "PXEClient" {
+    option routers 10.0.0.1;
+  } else {
+    default-lease-time 600;
+  }
+}
+"#,
+    );
+    assert!(res.is_ok(), "parse failed: {:?}", res.err());
+    let config = res.unwrap();
+    let host = config.hosts()[0];
+
+    assert_eq!(
+        writer::write_host(host).unwrap(),
+        r#"host "pxe client" {
+  hardware ethernet 00:11:22:33:44:55;
+  fixed-address6 2001:db8::10;
+  filename "pxelinux.0";
+  next-server 10.0.0.1;
+  if option vendor-class-identifier = "PXEClient" {
+    option routers 10.0.0.1;
+  } else {
+    default-lease-time 600;
+  }
+}
+"#
+    );
+
+    let res = parser::parse_config("host a {\n  group {\n  }\n}\n");
+    let config = res.unwrap();
+    assert_eq!(
+        writer::write_host(config.hosts()[0]).err(),
+        Some("line 2, column 3: Statement cannot be written to a leases file".to_owned())
+    );
+}